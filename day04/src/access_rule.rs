@@ -0,0 +1,31 @@
+use crate::neighborhood::Neighborhood;
+use crate::threshold::Threshold;
+
+#[derive(Debug, Clone)]
+pub struct AccessRule {
+    pub neighborhood: Neighborhood,
+    pub threshold: Threshold,
+    /// Treats the grid as a torus, so cells past one edge come back around
+    /// from the opposite edge.
+    pub wrap: bool,
+}
+
+impl AccessRule {
+    #[inline]
+    #[must_use]
+    pub fn new(neighborhood: Neighborhood, threshold: Threshold) -> Self {
+        Self {
+            neighborhood,
+            threshold,
+            wrap: false,
+        }
+    }
+}
+
+impl Default for AccessRule {
+    /// A roll is accessible when fewer than 4 of its 8 neighbors are rolls.
+    #[inline]
+    fn default() -> Self {
+        Self::new(Neighborhood::Moore(1), Threshold::LessThan(4))
+    }
+}
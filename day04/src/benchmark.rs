@@ -0,0 +1,49 @@
+use crate::access_rule::AccessRule;
+use crate::bit_grid::BitGrid;
+use crate::find_accessible_rolls;
+use std::time::Instant;
+
+/// Compares a single accessibility pass over a generated square grid using
+/// the plain and the bit-packed representations.
+pub fn run_benchmark(size: usize, rule: &AccessRule) {
+    if !BitGrid::supports(rule) {
+        println!("Benchmark - The bit-packed grid only supports the 8-cell neighborhood");
+        return;
+    }
+    let rolls = generate_rolls(size, size, 0x2025_1204);
+    println!("Benchmark - Generated a {size} x {size} grid");
+
+    let start = Instant::now();
+    let plain_count = find_accessible_rolls(&rolls, rule).len();
+    let plain_elapsed = start.elapsed();
+    println!("Benchmark - Vec<Vec<bool>>: {plain_count} accessible roll(s) in {plain_elapsed:?}");
+
+    let start = Instant::now();
+    let grid = BitGrid::from_rolls(&rolls);
+    let pack_elapsed = start.elapsed();
+    let start = Instant::now();
+    let packed_count = grid.find_accessible(rule.threshold).count_ones();
+    let packed_elapsed = start.elapsed();
+    println!(
+        "Benchmark - BitGrid: {packed_count} accessible roll(s) in {packed_elapsed:?} (packing took {pack_elapsed:?})"
+    );
+}
+
+/// Fills roughly 5 out of 8 cells with rolls, using a xorshift generator so
+/// runs are repeatable.
+#[must_use]
+pub fn generate_rolls(width: usize, height: usize, seed: u64) -> Vec<Vec<bool>> {
+    let mut state = seed.max(1);
+    let mut rolls = Vec::with_capacity(height);
+    for _ in 0..height {
+        let mut row = Vec::with_capacity(width);
+        for _ in 0..width {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            row.push(state % 8 < 5);
+        }
+        rolls.push(row);
+    }
+    rolls
+}
@@ -0,0 +1,217 @@
+use crate::access_rule::AccessRule;
+use crate::neighborhood::Neighborhood;
+use crate::threshold::Threshold;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Packs each row of rolls into 64-bit words, so neighbor counts can be
+/// computed for 64 cells at a time.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD_BITS);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    /// Rows shorter than the longest row are treated as empty past their end.
+    #[must_use]
+    pub fn from_rolls(rolls: &[Vec<bool>]) -> Self {
+        let width = rolls.iter().map(Vec::len).max().unwrap_or(0);
+        let mut grid = Self::new(width, rolls.len());
+        for (row_index, row) in rolls.iter().enumerate() {
+            for (column_index, is_roll) in row.iter().enumerate() {
+                if *is_roll {
+                    grid.set(row_index, column_index);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Only the 8-cell neighborhood without wrapping is packed, since that is
+    /// what the shifts below implement.
+    #[must_use]
+    pub fn supports(rule: &AccessRule) -> bool {
+        rule.neighborhood == Neighborhood::Moore(1) && !rule.wrap
+    }
+
+    #[inline]
+    pub fn set(&mut self, row_index: usize, column_index: usize) {
+        let index = row_index * self.words_per_row + column_index / WORD_BITS;
+        self.words[index] |= 1 << (column_index % WORD_BITS);
+    }
+
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Clears every cell that is set in the given mask.
+    pub fn remove(&mut self, mask: &Self) {
+        for (word, mask_word) in self.words.iter_mut().zip(&mask.words) {
+            *word &= !mask_word;
+        }
+    }
+
+    /// Lists the row and column of every set cell, in row-major order.
+    #[must_use]
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        for (index, word) in self.words.iter().enumerate() {
+            let row_index = index / self.words_per_row;
+            let base_column = (index % self.words_per_row) * WORD_BITS;
+            let mut remaining = *word;
+            while remaining != 0 {
+                let bit = remaining.trailing_zeros() as usize;
+                positions.push((row_index, base_column + bit));
+                remaining &= remaining - 1;
+            }
+        }
+        positions
+    }
+
+    /// Finds every roll whose count of rolls among its 8 neighbors meets the
+    /// threshold.
+    #[must_use]
+    pub fn find_accessible(&self, threshold: Threshold) -> Self {
+        // A neighbor count is 0 to 8, so only 9 comparisons are ever needed.
+        let matching_counts: Vec<u32> = (0..=8).filter(|c| threshold.is_met(*c)).collect();
+        let mut accessible = Self::new(self.width, self.height);
+        for row_index in 0..self.height {
+            for word_index in 0..self.words_per_row {
+                let counts = self.count_neighbors(row_index, word_index);
+                let mut met = 0u64;
+                for count in &matching_counts {
+                    met |= counts.equal_to(*count);
+                }
+                let index = row_index * self.words_per_row + word_index;
+                accessible.words[index] = self.words[index] & met;
+            }
+        }
+        accessible
+    }
+
+    fn count_neighbors(&self, row_index: usize, word_index: usize) -> BitSlicedCount {
+        let mut counts = BitSlicedCount::default();
+        let rows = [
+            row_index.checked_sub(1),
+            Some(row_index),
+            Some(row_index + 1).filter(|r| *r < self.height),
+        ];
+        for (offset, row) in rows.into_iter().enumerate() {
+            let Some(row) = row else {
+                continue;
+            };
+            let current = self.word(row, Some(word_index));
+            let previous = self.word(row, word_index.checked_sub(1));
+            let next = self.word(row, Some(word_index + 1));
+            // Each cell sees its left neighbor by shifting towards higher
+            // columns, carrying in the top bit of the previous word.
+            counts.add((current << 1) | (previous >> (WORD_BITS - 1)));
+            counts.add((current >> 1) | (next << (WORD_BITS - 1)));
+            if offset != 1 {
+                counts.add(current);
+            }
+        }
+        counts
+    }
+
+    #[inline]
+    fn word(&self, row_index: usize, word_index: Option<usize>) -> u64 {
+        match word_index {
+            Some(word_index) if word_index < self.words_per_row => {
+                self.words[row_index * self.words_per_row + word_index]
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Keeps a 4-bit counter for each of 64 cells, one word per bit.
+#[derive(Debug, Default)]
+struct BitSlicedCount {
+    bits: [u64; 4],
+}
+
+impl BitSlicedCount {
+    /// Adds 1 to every cell set in the plane, rippling the carry through the
+    /// counter bits.
+    #[inline]
+    fn add(&mut self, plane: u64) {
+        let mut carry = plane;
+        for bit in &mut self.bits {
+            let next_carry = *bit & carry;
+            *bit ^= carry;
+            carry = next_carry;
+        }
+    }
+
+    #[inline]
+    fn equal_to(&self, count: u32) -> u64 {
+        let mut mask = u64::MAX;
+        for (index, bit) in self.bits.iter().enumerate() {
+            if count & (1 << index) == 0 {
+                mask &= !bit;
+            } else {
+                mask &= bit;
+            }
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_grid::BitGrid;
+    use crate::threshold::Threshold;
+
+    #[test]
+    fn test_positions_round_trip() {
+        let rolls = vec![vec![false; 70], vec![true; 130]];
+        let grid = BitGrid::from_rolls(&rolls);
+        assert_eq!(130, grid.count_ones());
+        let positions = grid.positions();
+        assert_eq!((1, 0), positions[0]);
+        assert_eq!((1, 64), positions[64]);
+        assert_eq!((1, 129), positions[129]);
+    }
+
+    #[test]
+    fn test_neighbors_across_word_boundary() {
+        // Cells 63 and 64 live in different words but are still neighbors.
+        let mut rolls = vec![vec![false; 128]; 2];
+        rolls[0][63] = true;
+        rolls[0][64] = true;
+        rolls[1][64] = true;
+        let grid = BitGrid::from_rolls(&rolls);
+        let accessible = grid.find_accessible(Threshold::Equal(2));
+        assert_eq!(vec![(0, 63), (0, 64), (1, 64)], accessible.positions());
+        let accessible = grid.find_accessible(Threshold::LessThan(2));
+        assert_eq!(0, accessible.count_ones());
+    }
+
+    #[test]
+    fn test_remove() {
+        let rolls = vec![vec![true; 3]];
+        let mut grid = BitGrid::from_rolls(&rolls);
+        let accessible = grid.find_accessible(Threshold::LessThan(2));
+        grid.remove(&accessible);
+        assert_eq!(vec![(0, 1)], grid.positions());
+    }
+}
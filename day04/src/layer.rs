@@ -0,0 +1,23 @@
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Layer {
+    /// The cell never held a roll.
+    Empty,
+    /// The roll was removed in the given round, starting at 0.
+    Removed(usize),
+    /// The roll was still stuck when no more rolls could be removed.
+    Stuck,
+}
+
+impl Layer {
+    /// Labels removal waves `1`-`9`, then `a`-`z`, then `A`-`Z`, and any
+    /// later wave as `#`.
+    #[must_use]
+    pub fn to_char(self) -> char {
+        const LABELS: &[u8] = b"123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        match self {
+            Self::Empty => '.',
+            Self::Stuck => '@',
+            Self::Removed(round) => LABELS.get(round).map_or('#', |label| char::from(*label)),
+        }
+    }
+}
@@ -1,70 +1,122 @@
+mod access_rule;
+#[cfg(feature = "bit-grid")]
+mod benchmark;
+#[cfg(feature = "bit-grid")]
+mod bit_grid;
+mod layer;
+mod neighborhood;
+mod options;
+mod removal_timeline;
+mod threshold;
+
+use crate::access_rule::AccessRule;
+#[cfg(feature = "bit-grid")]
+use crate::bit_grid::BitGrid;
+use crate::options::Options;
+use crate::removal_timeline::RemovalTimeline;
 use shared::AdventError;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 fn main() -> Result<(), AdventError> {
     let path = Path::new("day04/resources/input.txt");
-    run_first_part(path)?;
-    run_second_part(path)?;
+    let options = Options::parse(std::env::args().skip(1))?;
+    #[cfg(feature = "bit-grid")]
+    if let Some(size) = options.bench_size {
+        benchmark::run_benchmark(size, &options.rule);
+        return Ok(());
+    }
+    run_first_part(path, &options.rule)?;
+    run_second_part(path, &options)?;
     Ok(())
 }
 
-fn run_first_part(path: &Path) -> Result<(), AdventError> {
+fn run_first_part(path: &Path, rule: &AccessRule) -> Result<(), AdventError> {
     let rolls = read_roll_lines(path)?;
-    let accessible_rolls = count_accessible_rolls(&rolls);
+    let accessible_rolls = count_accessible_rolls(&rolls, rule);
     println!("Part 1 - There are {accessible_rolls} accessible roll(s)");
     Ok(())
 }
 
-fn count_accessible_rolls(rolls: &[Vec<bool>]) -> u32 {
-    let mut accessible_rolls = 0u32;
-    for (row_index, row) in rolls.iter().enumerate() {
-        for (column_index, column) in row.iter().enumerate() {
-            if !*column {
-                continue;
-            }
-            let occupied_count = count_adjacent_rolls(rolls, row_index, column_index);
-            if occupied_count < 4 {
-                accessible_rolls += 1;
-            }
-        }
+fn count_accessible_rolls(rolls: &[Vec<bool>], rule: &AccessRule) -> usize {
+    #[cfg(feature = "bit-grid")]
+    if BitGrid::supports(rule) {
+        let grid = BitGrid::from_rolls(rolls);
+        return grid.find_accessible(rule.threshold).count_ones();
     }
-    accessible_rolls
+    find_accessible_rolls(rolls, rule).len()
 }
 
-fn run_second_part(path: &Path) -> Result<(), AdventError> {
+fn run_second_part(path: &Path, options: &Options) -> Result<(), AdventError> {
     let mut rolls = read_roll_lines(path)?;
-    let moved_rolls = count_accessible_rolls_repeatedly(&mut rolls);
+    let timeline = remove_accessible_rolls_repeatedly(&mut rolls, &options.rule);
+    let moved_rolls = timeline.total_removed();
     println!("Part 2 - There are {moved_rolls} moved roll(s)");
+    for (round, removed) in timeline.rounds().iter().enumerate() {
+        println!("Part 2 - Round {} removed {removed} roll(s)", round + 1);
+    }
+    println!(
+        "Part 2 - There are {} stuck roll(s)",
+        timeline.stuck_count()
+    );
+    if options.render_layers {
+        print!("{timeline}");
+    }
     Ok(())
 }
 
-fn count_accessible_rolls_repeatedly(rolls: &mut [Vec<bool>]) -> usize {
-    let mut moved_rolls = 0usize;
+fn remove_accessible_rolls_repeatedly(
+    rolls: &mut [Vec<bool>],
+    rule: &AccessRule,
+) -> RemovalTimeline {
+    let mut timeline = RemovalTimeline::new(rolls);
+    #[cfg(feature = "bit-grid")]
+    if BitGrid::supports(rule) {
+        let mut grid = BitGrid::from_rolls(rolls);
+        loop {
+            let accessible = grid.find_accessible(rule.threshold);
+            let accessible_rolls = accessible.positions();
+            if accessible_rolls.is_empty() {
+                break;
+            }
+            for (row_index, column_index) in accessible_rolls.iter().copied() {
+                rolls[row_index][column_index] = false;
+            }
+            grid.remove(&accessible);
+            timeline.record_round(&accessible_rolls);
+        }
+        return timeline;
+    }
     loop {
-        let accessible_rolls = find_accessible_rolls(rolls);
-        let accessible_roll_count = accessible_rolls.len();
-        if accessible_roll_count == 0 {
+        let accessible_rolls = find_accessible_rolls(rolls, rule);
+        if accessible_rolls.is_empty() {
             break;
         }
-        moved_rolls += accessible_roll_count;
-        for (row_index, column_index) in accessible_rolls {
+        for (row_index, column_index) in accessible_rolls.iter().copied() {
             rolls[row_index][column_index] = false;
         }
+        timeline.record_round(&accessible_rolls);
     }
-    moved_rolls
+    timeline
 }
 
-fn find_accessible_rolls(rolls: &[Vec<bool>]) -> Vec<(usize, usize)> {
+fn find_accessible_rolls(rolls: &[Vec<bool>], rule: &AccessRule) -> Vec<(usize, usize)> {
+    let offsets = rule.neighborhood.offsets();
+    let mut wrapped_cells = HashSet::new();
     let mut accessible_rolls = Vec::new();
     for (row_index, row) in rolls.iter().enumerate() {
         for (column_index, column) in row.iter().enumerate() {
             if !*column {
                 continue;
             }
-            let occupied_count = count_adjacent_rolls(rolls, row_index, column_index);
-            if occupied_count < 4 {
+            let occupied_count = if rule.wrap {
+                count_wrapped_rolls(rolls, &offsets, &mut wrapped_cells, row_index, column_index)
+            } else {
+                count_adjacent_rolls(rolls, &offsets, row_index, column_index)
+            };
+            if rule.threshold.is_met(occupied_count) {
                 accessible_rolls.push((row_index, column_index));
             }
         }
@@ -72,32 +124,62 @@ fn find_accessible_rolls(rolls: &[Vec<bool>]) -> Vec<(usize, usize)> {
     accessible_rolls
 }
 
-fn count_adjacent_rolls(rolls: &[Vec<bool>], row_index: usize, column_index: usize) -> u32 {
-    let row = &rolls[row_index];
+fn count_adjacent_rolls(
+    rolls: &[Vec<bool>],
+    offsets: &[(isize, isize, u32)],
+    row_index: usize,
+    column_index: usize,
+) -> u32 {
+    let row_count = rolls.len().cast_signed();
     let mut occupied_count = 0u32;
-    let start_row = row_index as isize - 1;
-    let end_row = row_index as isize + 1;
-    let start_column = column_index as isize - 1;
-    let end_column = column_index as isize + 1;
-    for adjacent_row_index in start_row..=end_row {
-        // Ignore rows that are out of bounds.
-        if adjacent_row_index < 0 || adjacent_row_index >= rolls.len() as isize {
+    for &(row_offset, column_offset, weight) in offsets {
+        let adjacent_row_index = row_index.cast_signed() + row_offset;
+        let adjacent_column_index = column_index.cast_signed() + column_offset;
+        // Ignore cells that are out of bounds.
+        if adjacent_row_index < 0 || adjacent_row_index >= row_count {
             continue;
         }
-        for adjacent_column_index in start_column..=end_column {
-            // Ignore columns that are out of bounds.
-            if adjacent_column_index < 0 || adjacent_column_index >= row.len() as isize {
-                continue;
-            }
-            // Don't look at the current value.
-            if adjacent_row_index == row_index as isize
-                && adjacent_column_index == column_index as isize
-            {
-                continue;
-            }
-            if rolls[adjacent_row_index as usize][adjacent_column_index as usize] {
-                occupied_count += 1;
-            }
+        let adjacent_row = &rolls[adjacent_row_index.cast_unsigned()];
+        if adjacent_column_index < 0 || adjacent_column_index >= adjacent_row.len().cast_signed() {
+            continue;
+        }
+        if adjacent_row[adjacent_column_index.cast_unsigned()] {
+            occupied_count = occupied_count.saturating_add(weight);
+        }
+    }
+    occupied_count
+}
+
+/// Counts like `count_adjacent_rolls`, but wraps around the edges. When the
+/// neighborhood is as large as the grid, several offsets can land on the same
+/// cell, which then counts once with the weight of the first offset, and any
+/// that land on the roll itself are skipped.
+fn count_wrapped_rolls(
+    rolls: &[Vec<bool>],
+    offsets: &[(isize, isize, u32)],
+    visited: &mut HashSet<(usize, usize)>,
+    row_index: usize,
+    column_index: usize,
+) -> u32 {
+    let row_count = rolls.len().cast_signed();
+    let column_count = rolls[row_index].len().cast_signed();
+    visited.clear();
+    visited.insert((row_index, column_index));
+    let mut occupied_count = 0u32;
+    for &(row_offset, column_offset, weight) in offsets {
+        let adjacent_row_index = (row_index.cast_signed() + row_offset)
+            .rem_euclid(row_count)
+            .cast_unsigned();
+        let adjacent_column_index = (column_index.cast_signed() + column_offset)
+            .rem_euclid(column_count)
+            .cast_unsigned();
+        if !visited.insert((adjacent_row_index, adjacent_column_index)) {
+            continue;
+        }
+        // Rows can be shorter than the row being checked.
+        let adjacent_row = &rolls[adjacent_row_index];
+        if adjacent_row.get(adjacent_column_index) == Some(&true) {
+            occupied_count = occupied_count.saturating_add(weight);
         }
     }
     occupied_count
@@ -133,23 +215,141 @@ fn read_roll_lines_direct<R: BufRead>(reader: R) -> Result<Vec<Vec<bool>>, Adven
 
 #[cfg(test)]
 mod tests {
+    use crate::access_rule::AccessRule;
+    use crate::layer::Layer;
+    use crate::neighborhood::Neighborhood;
+    use crate::threshold::Threshold;
     use crate::{
-        count_accessible_rolls, count_accessible_rolls_repeatedly, read_roll_lines_direct,
+        count_accessible_rolls, read_roll_lines_direct, remove_accessible_rolls_repeatedly,
     };
     use std::io::Cursor;
 
     #[test]
     fn test_part1_example() {
         let rolls = read_test_data();
-        let count = count_accessible_rolls(&rolls);
+        let count = count_accessible_rolls(&rolls, &AccessRule::default());
         assert_eq!(13, count);
     }
 
     #[test]
     fn test_part2_example() {
         let mut rolls = read_test_data();
-        let count = count_accessible_rolls_repeatedly(&mut rolls);
-        assert_eq!(43, count);
+        let timeline = remove_accessible_rolls_repeatedly(&mut rolls, &AccessRule::default());
+        assert_eq!(43, timeline.total_removed());
+    }
+
+    #[test]
+    fn test_von_neumann_rule() {
+        let rolls = read_roll_lines_direct(Cursor::new(".@.\n@@@\n.@.")).unwrap();
+        let rule = AccessRule::new(Neighborhood::VonNeumann(1), Threshold::LessThan(2));
+        // Only the four arms have a single orthogonal neighbor.
+        assert_eq!(4, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_radius_2_rule() {
+        let rolls = read_roll_lines_direct(Cursor::new("@.@.@")).unwrap();
+        let rule = AccessRule::new(Neighborhood::Moore(2), Threshold::LessThan(2));
+        // The middle roll sees both ends within two columns.
+        assert_eq!(2, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_weighted_kernel_rule() {
+        let rolls = read_roll_lines_direct(Cursor::new("@@\n@.")).unwrap();
+        let kernel =
+            Neighborhood::kernel(vec![vec![0, 3, 0], vec![1, 0, 1], vec![0, 3, 0]]).unwrap();
+        let rule = AccessRule::new(kernel, Threshold::LessThanOrEqual(1));
+        // The top-right roll only has a horizontal neighbor.
+        assert_eq!(1, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_wrapped_rule() {
+        let rolls = read_roll_lines_direct(Cursor::new("@..@")).unwrap();
+        let mut rule = AccessRule::new(Neighborhood::Moore(1), Threshold::LessThan(1));
+        assert_eq!(2, count_accessible_rolls(&rolls, &rule));
+        rule.wrap = true;
+        assert_eq!(0, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_wrapped_single_row() {
+        // Every offset lands back on the same row, and some on the roll itself.
+        let rolls = read_roll_lines_direct(Cursor::new("@@.")).unwrap();
+        let rule = AccessRule {
+            wrap: true,
+            ..AccessRule::new(Neighborhood::Moore(1), Threshold::LessThan(2))
+        };
+        assert_eq!(2, count_accessible_rolls(&rolls, &rule));
+        let rule = AccessRule {
+            wrap: true,
+            ..AccessRule::new(Neighborhood::Moore(3), Threshold::LessThan(2))
+        };
+        assert_eq!(2, count_accessible_rolls(&rolls, &rule));
+
+        // Each roll sees the other one once, not once per wrapped offset.
+        let rolls = read_roll_lines_direct(Cursor::new("@@")).unwrap();
+        let rule = AccessRule {
+            wrap: true,
+            ..AccessRule::new(Neighborhood::Moore(2), Threshold::LessThanOrEqual(1))
+        };
+        assert_eq!(2, count_accessible_rolls(&rolls, &rule));
+        let rule = AccessRule {
+            wrap: true,
+            ..AccessRule::new(Neighborhood::Moore(2), Threshold::LessThan(1))
+        };
+        assert_eq!(0, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_heavy_weights_saturate() {
+        let rolls = read_roll_lines_direct(Cursor::new("@@@")).unwrap();
+        let kernel = Neighborhood::kernel(vec![
+            vec![0, 0, 0],
+            vec![u32::MAX, 0, u32::MAX],
+            vec![0, 0, 0],
+        ])
+        .unwrap();
+        let rule = AccessRule::new(kernel, Threshold::LessThan(u32::MAX));
+        // The middle roll saturates rather than overflowing.
+        assert_eq!(0, count_accessible_rolls(&rolls, &rule));
+    }
+
+    #[test]
+    fn test_part2_example_timeline() {
+        let mut rolls = read_test_data();
+        let timeline = remove_accessible_rolls_repeatedly(&mut rolls, &AccessRule::default());
+        assert_eq!(&[13, 12, 7, 5, 2, 1, 1, 1, 1], timeline.rounds());
+        assert_eq!(43, timeline.total_removed());
+        assert_eq!(28, timeline.stuck_count());
+        assert_eq!(Layer::Empty, timeline.layers()[0][0]);
+        assert_eq!(Layer::Removed(0), timeline.layers()[0][2]);
+    }
+
+    #[test]
+    fn test_render_timeline() {
+        let mut rolls = read_roll_lines_direct(Cursor::new("@@@\n@@@\n@@.")).unwrap();
+        let timeline = remove_accessible_rolls_repeatedly(&mut rolls, &AccessRule::default());
+        assert_eq!("121\n232\n12.\n", timeline.to_string());
+    }
+
+    #[cfg(feature = "bit-grid")]
+    #[test]
+    fn test_bit_grid_matches_plain_grid() {
+        use crate::benchmark::generate_rolls;
+        use crate::bit_grid::BitGrid;
+        use crate::find_accessible_rolls;
+
+        let rolls = generate_rolls(150, 40, 7);
+        let grid = BitGrid::from_rolls(&rolls);
+        for limit in 0..=9 {
+            let threshold = Threshold::LessThan(limit);
+            let rule = AccessRule::new(Neighborhood::Moore(1), threshold);
+            let expected = find_accessible_rolls(&rolls, &rule);
+            let actual = grid.find_accessible(threshold).positions();
+            assert_eq!(expected, actual);
+        }
     }
 
     fn read_test_data() -> Vec<Vec<bool>> {
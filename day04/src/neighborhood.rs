@@ -0,0 +1,185 @@
+use shared::AdventError;
+
+/// The largest radius accepted for a Moore or von Neumann neighborhood. A
+/// radius of `r` visits `(2r + 1)²` offsets per cell, and anything wider
+/// than the grid adds nothing but work.
+pub const MAX_RADIUS: usize = 100;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Neighborhood {
+    /// All cells within the given radius, including diagonals.
+    Moore(usize),
+    /// Cells within the given Manhattan distance, so no diagonals at radius 1.
+    VonNeumann(usize),
+    /// A square grid of weights centered on the current cell.
+    Kernel(Vec<Vec<u32>>),
+}
+
+impl Neighborhood {
+    /// Creates a weighted kernel, which must be a square with an odd side
+    /// length so the current cell sits in the middle. The center weight is
+    /// ignored, since a roll never counts as its own neighbor.
+    pub fn kernel(weights: Vec<Vec<u32>>) -> Result<Self, AdventError> {
+        let size = weights.len();
+        if size.is_multiple_of(2) {
+            return Err(AdventError::new("A kernel must have an odd number of rows"));
+        }
+        if weights.iter().any(|row| row.len() != size) {
+            return Err(AdventError::new("A kernel must be square"));
+        }
+        Ok(Self::Kernel(weights))
+    }
+
+    /// Parses `moore:<radius>`, `von-neumann:<radius>` or
+    /// `kernel:<row>/<row>/...`, where each row is a comma-separated list of
+    /// weights. A radius may be at most [`MAX_RADIUS`].
+    pub fn parse_str(value: &str) -> Result<Self, AdventError> {
+        let invalid = || AdventError::new("Encountered an invalid neighborhood");
+        let (kind, argument) = value.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "moore" => parse_radius(argument).map(Self::Moore),
+            "von-neumann" => parse_radius(argument).map(Self::VonNeumann),
+            "kernel" => {
+                let mut weights = Vec::new();
+                for row in argument.split('/') {
+                    let row: Option<Vec<u32>> = row
+                        .split(',')
+                        .map(|w| w.trim().parse::<u32>().ok())
+                        .collect();
+                    weights.push(row.ok_or_else(invalid)?);
+                }
+                Self::kernel(weights)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Lists the row offset, column offset and weight of every neighboring
+    /// cell, excluding the current cell.
+    #[must_use]
+    pub fn offsets(&self) -> Vec<(isize, isize, u32)> {
+        let mut offsets = Vec::new();
+        match self {
+            Self::Moore(radius) | Self::VonNeumann(radius) => {
+                let radius = radius.cast_signed();
+                for row_offset in -radius..=radius {
+                    for column_offset in -radius..=radius {
+                        if row_offset == 0 && column_offset == 0 {
+                            continue;
+                        }
+                        if matches!(self, Self::VonNeumann(_))
+                            && row_offset.abs() + column_offset.abs() > radius
+                        {
+                            continue;
+                        }
+                        offsets.push((row_offset, column_offset, 1));
+                    }
+                }
+            }
+            Self::Kernel(weights) => {
+                let radius = (weights.len() / 2).cast_signed();
+                for (row_index, row) in weights.iter().enumerate() {
+                    for (column_index, weight) in row.iter().enumerate() {
+                        let row_offset = row_index.cast_signed() - radius;
+                        let column_offset = column_index.cast_signed() - radius;
+                        if (row_offset == 0 && column_offset == 0) || *weight == 0 {
+                            continue;
+                        }
+                        offsets.push((row_offset, column_offset, *weight));
+                    }
+                }
+            }
+        }
+        offsets
+    }
+}
+
+fn parse_radius(value: &str) -> Result<usize, AdventError> {
+    let Ok(radius) = value.parse::<usize>() else {
+        return Err(AdventError::new("Encountered an invalid neighborhood"));
+    };
+    if radius > MAX_RADIUS {
+        return Err(AdventError::new(format!(
+            "The neighborhood radius must be at most {MAX_RADIUS}"
+        )));
+    }
+    Ok(radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::neighborhood::{MAX_RADIUS, Neighborhood};
+
+    #[test]
+    fn test_moore_radius_1_offsets() {
+        assert_eq!(8, Neighborhood::Moore(1).offsets().len());
+    }
+
+    #[test]
+    fn test_moore_radius_2_offsets() {
+        assert_eq!(24, Neighborhood::Moore(2).offsets().len());
+    }
+
+    #[test]
+    fn test_von_neumann_radius_1_offsets() {
+        let offsets = Neighborhood::VonNeumann(1).offsets();
+        assert_eq!(4, offsets.len());
+        assert!(offsets.iter().all(|(r, c, _)| r.abs() + c.abs() == 1));
+    }
+
+    #[test]
+    fn test_von_neumann_radius_2_offsets() {
+        assert_eq!(12, Neighborhood::VonNeumann(2).offsets().len());
+    }
+
+    #[test]
+    fn test_kernel_skips_center_and_zero_weights() {
+        let kernel =
+            Neighborhood::kernel(vec![vec![0, 2, 0], vec![1, 9, 1], vec![0, 2, 0]]).unwrap();
+        let offsets = kernel.offsets();
+        assert_eq!(4, offsets.len());
+        assert!(offsets.contains(&(-1, 0, 2)));
+        assert!(offsets.contains(&(0, 1, 1)));
+    }
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(
+            Neighborhood::Moore(2),
+            Neighborhood::parse_str("moore:2").unwrap()
+        );
+        assert_eq!(
+            Neighborhood::VonNeumann(1),
+            Neighborhood::parse_str("von-neumann:1").unwrap()
+        );
+        assert_eq!(
+            Neighborhood::Kernel(vec![vec![1, 2, 1], vec![2, 0, 2], vec![1, 2, 1]]),
+            Neighborhood::parse_str("kernel:1,2,1/2,0,2/1,2,1").unwrap()
+        );
+        assert!(Neighborhood::parse_str("kernel:1,2/2,1").is_err());
+        assert!(Neighborhood::parse_str("kernel:1,x,1/1,1,1/1,1,1").is_err());
+        assert!(Neighborhood::parse_str("hex:1").is_err());
+    }
+
+    #[test]
+    fn test_parse_str_caps_radius() {
+        let radius = format!("moore:{MAX_RADIUS}");
+        assert_eq!(
+            Neighborhood::Moore(MAX_RADIUS),
+            Neighborhood::parse_str(&radius).unwrap()
+        );
+        for value in ["moore:101", "von-neumann:18446744073709551615"] {
+            let error = Neighborhood::parse_str(value).unwrap_err();
+            assert_eq!(
+                "The neighborhood radius must be at most 100",
+                error.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_kernel_must_be_odd_square() {
+        assert!(Neighborhood::kernel(vec![vec![1, 1], vec![1, 1]]).is_err());
+        assert!(Neighborhood::kernel(vec![vec![1, 1, 1], vec![1], vec![1, 1, 1]]).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::access_rule::AccessRule;
+use crate::neighborhood::Neighborhood;
+use crate::threshold::Threshold;
+use shared::AdventError;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub rule: AccessRule,
+    pub render_layers: bool,
+    #[cfg(feature = "bit-grid")]
+    pub bench_size: Option<usize>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, AdventError> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--neighborhood" => {
+                    let value = args.next().unwrap_or_default();
+                    options.rule.neighborhood = Neighborhood::parse_str(&value)?;
+                }
+                "--threshold" => {
+                    let value = args.next().unwrap_or_default();
+                    let Some(threshold) = Threshold::parse_str(&value) else {
+                        return Err(AdventError::new("Encountered an invalid threshold"));
+                    };
+                    options.rule.threshold = threshold;
+                }
+                "--wrap" => options.rule.wrap = true,
+                "--layers" => options.render_layers = true,
+                #[cfg(feature = "bit-grid")]
+                "--bench" => {
+                    let value = args.next().unwrap_or_default();
+                    let Ok(size) = value.parse::<usize>() else {
+                        return Err(AdventError::new("Encountered an invalid benchmark size"));
+                    };
+                    options.bench_size = Some(size);
+                }
+                _ => return Err(AdventError::new("Encountered an unknown argument")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::neighborhood::Neighborhood;
+    use crate::options::Options;
+    use crate::threshold::Threshold;
+
+    #[test]
+    fn test_parse() {
+        let args = [
+            "--neighborhood",
+            "von-neumann:2",
+            "--threshold",
+            ">=3",
+            "--wrap",
+            "--layers",
+        ];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(Neighborhood::VonNeumann(2), options.rule.neighborhood);
+        assert_eq!(Threshold::GreaterThanOrEqual(3), options.rule.threshold);
+        assert!(options.rule.wrap);
+        assert!(options.render_layers);
+    }
+
+    #[test]
+    fn test_parse_large_radius() {
+        let args = ["--neighborhood", "moore:1000000"];
+        let error = Options::parse(args.into_iter().map(String::from)).unwrap_err();
+        assert_eq!(
+            "The neighborhood radius must be at most 100",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_argument() {
+        let args = ["--bogus"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+    }
+}
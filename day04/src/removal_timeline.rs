@@ -0,0 +1,72 @@
+use crate::layer::Layer;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub struct RemovalTimeline {
+    rounds: Vec<usize>,
+    layers: Vec<Vec<Layer>>,
+}
+
+impl RemovalTimeline {
+    /// Starts a timeline where every roll is stuck until it gets removed.
+    #[must_use]
+    pub fn new(rolls: &[Vec<bool>]) -> Self {
+        let layers = rolls
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|is_roll| if *is_roll { Layer::Stuck } else { Layer::Empty })
+                    .collect()
+            })
+            .collect();
+        Self {
+            rounds: Vec::new(),
+            layers,
+        }
+    }
+
+    pub fn record_round(&mut self, removed_rolls: &[(usize, usize)]) {
+        let round = self.rounds.len();
+        for (row_index, column_index) in removed_rolls {
+            self.layers[*row_index][*column_index] = Layer::Removed(round);
+        }
+        self.rounds.push(removed_rolls.len());
+    }
+
+    /// The number of rolls removed in each round.
+    #[inline]
+    #[must_use]
+    pub fn rounds(&self) -> &[usize] {
+        &self.rounds
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn layers(&self) -> &[Vec<Layer>] {
+        &self.layers
+    }
+
+    #[must_use]
+    pub fn total_removed(&self) -> usize {
+        self.rounds.iter().sum()
+    }
+
+    #[must_use]
+    pub fn stuck_count(&self) -> usize {
+        self.layers
+            .iter()
+            .flatten()
+            .filter(|layer| **layer == Layer::Stuck)
+            .count()
+    }
+}
+
+impl Display for RemovalTimeline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.layers() {
+            let line: String = row.iter().map(|layer| layer.to_char()).collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Threshold {
+    LessThan(u32),
+    LessThanOrEqual(u32),
+    GreaterThan(u32),
+    GreaterThanOrEqual(u32),
+    Equal(u32),
+}
+
+impl Threshold {
+    /// Parses a comparison followed by a limit, such as `<4` or `>=2`.
+    #[must_use]
+    pub fn parse_str(value: &str) -> Option<Self> {
+        let (constructor, limit): (fn(u32) -> Self, &str) =
+            if let Some(limit) = value.strip_prefix("<=") {
+                (Self::LessThanOrEqual, limit)
+            } else if let Some(limit) = value.strip_prefix(">=") {
+                (Self::GreaterThanOrEqual, limit)
+            } else if let Some(limit) = value.strip_prefix('<') {
+                (Self::LessThan, limit)
+            } else if let Some(limit) = value.strip_prefix('>') {
+                (Self::GreaterThan, limit)
+            } else if let Some(limit) = value.strip_prefix('=') {
+                (Self::Equal, limit)
+            } else {
+                return None;
+            };
+        let limit = limit.parse::<u32>().ok()?;
+        Some(constructor(limit))
+    }
+
+    #[must_use]
+    pub fn is_met(self, count: u32) -> bool {
+        match self {
+            Self::LessThan(limit) => count < limit,
+            Self::LessThanOrEqual(limit) => count <= limit,
+            Self::GreaterThan(limit) => count > limit,
+            Self::GreaterThanOrEqual(limit) => count >= limit,
+            Self::Equal(limit) => count == limit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::threshold::Threshold;
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(Some(Threshold::LessThan(4)), Threshold::parse_str("<4"));
+        assert_eq!(
+            Some(Threshold::LessThanOrEqual(4)),
+            Threshold::parse_str("<=4")
+        );
+        assert_eq!(Some(Threshold::GreaterThan(1)), Threshold::parse_str(">1"));
+        assert_eq!(
+            Some(Threshold::GreaterThanOrEqual(2)),
+            Threshold::parse_str(">=2")
+        );
+        assert_eq!(Some(Threshold::Equal(0)), Threshold::parse_str("=0"));
+        assert_eq!(None, Threshold::parse_str("4"));
+        assert_eq!(None, Threshold::parse_str("<x"));
+    }
+
+    #[test]
+    fn test_is_met() {
+        assert!(Threshold::LessThan(4).is_met(3));
+        assert!(!Threshold::LessThan(4).is_met(4));
+        assert!(Threshold::GreaterThanOrEqual(4).is_met(4));
+    }
+}
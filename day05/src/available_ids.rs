@@ -0,0 +1,139 @@
+use crate::advent_error::AdventError;
+use crate::interval_set::IntervalSet;
+use crate::stream_options::StreamOptions;
+use std::io::BufRead;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Parses the available ID section one line at a time, handing each ID to the
+/// callback instead of collecting them.
+pub fn for_each_available_id<R: BufRead>(
+    reader: R,
+    first_line_number: usize,
+    mut callback: impl FnMut(u64),
+) -> Result<(), AdventError> {
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = first_line_number + index;
+        let Ok(line) = line else {
+            return Err(AdventError::new(format!(
+                "Line {line_number}: Could not read the next line"
+            )));
+        };
+        let Ok(available_id) = line.trim_ascii_end().parse::<u64>() else {
+            return Err(AdventError::new(format!(
+                "Line {line_number}: Encountered an invalid available ID"
+            )));
+        };
+        callback(available_id);
+    }
+    Ok(())
+}
+
+/// Counts the streamed available IDs that fall in the fresh ranges. Memory
+/// use is bounded by the ranges and, when sharding, one chunk per thread.
+pub fn count_fresh_ids<R: BufRead>(
+    reader: R,
+    first_line_number: usize,
+    fresh_ranges: &IntervalSet,
+    options: StreamOptions,
+) -> Result<usize, AdventError> {
+    if options.threads <= 1 {
+        let mut fresh_count = 0usize;
+        for_each_available_id(reader, first_line_number, |id| {
+            if fresh_ranges.contains(id) {
+                fresh_count += 1;
+            }
+        })?;
+        return Ok(fresh_count);
+    }
+
+    let fresh_count = AtomicUsize::new(0);
+    // The channel only holds one chunk per thread, so a slow worker holds
+    // the reader back rather than letting chunks pile up.
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u64>>(options.threads);
+    let receiver = Mutex::new(receiver);
+    let result = thread::scope(|scope| {
+        for _ in 0..options.threads {
+            scope.spawn(|| {
+                loop {
+                    let chunk = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    let Ok(chunk) = chunk else {
+                        break;
+                    };
+                    let count = chunk
+                        .iter()
+                        .filter(|id| fresh_ranges.contains(**id))
+                        .count();
+                    fresh_count.fetch_add(count, Ordering::Relaxed);
+                }
+            });
+        }
+
+        let mut chunk = Vec::with_capacity(options.chunk_size);
+        let result = for_each_available_id(reader, first_line_number, |id| {
+            chunk.push(id);
+            if chunk.len() == options.chunk_size {
+                let full_chunk =
+                    std::mem::replace(&mut chunk, Vec::with_capacity(options.chunk_size));
+                // Sending only fails once every worker has stopped.
+                let _ = sender.send(full_chunk);
+            }
+        });
+        if !chunk.is_empty() {
+            let _ = sender.send(chunk);
+        }
+        // Closing the channel lets the workers finish.
+        drop(sender);
+        result
+    });
+    result?;
+    Ok(fresh_count.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::available_ids::count_fresh_ids;
+    use crate::fresh_range::FreshRange;
+    use crate::interval_set::IntervalSet;
+    use crate::stream_options::StreamOptions;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_count_fresh_ids_sharded() {
+        let ranges = [
+            FreshRange { start: 10, end: 19 },
+            FreshRange { start: 50, end: 99 },
+        ];
+        let set = IntervalSet::from_ranges(&ranges, false);
+        let ids: Vec<String> = (0..1_000).map(|id| (id % 200).to_string()).collect();
+        let input = ids.join("\n");
+        let sequential =
+            count_fresh_ids(Cursor::new(&input), 1, &set, StreamOptions::default()).unwrap();
+        let options = StreamOptions {
+            threads: 4,
+            chunk_size: 7,
+        };
+        let sharded = count_fresh_ids(Cursor::new(&input), 1, &set, options).unwrap();
+        assert_eq!(300, sequential);
+        assert_eq!(sequential, sharded);
+    }
+
+    #[test]
+    fn test_count_fresh_ids_sharded_reports_line() {
+        let set = IntervalSet::default();
+        let options = StreamOptions {
+            threads: 2,
+            chunk_size: 1,
+        };
+        let error = count_fresh_ids(Cursor::new("1\n2\nx\n4"), 7, &set, options).unwrap_err();
+        assert_eq!(
+            "Line 9: Encountered an invalid available ID",
+            error.to_string()
+        );
+    }
+}
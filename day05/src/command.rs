@@ -0,0 +1,68 @@
+use crate::advent_error::AdventError;
+use crate::fresh_range::FreshRange;
+use crate::parse_fresh_range;
+
+#[derive(Debug, Copy, Clone)]
+pub enum Command {
+    Add(FreshRange),
+    Remove(FreshRange),
+    Query(u64),
+    Count,
+}
+
+impl Command {
+    pub fn parse(line: &str, swap_inverted: bool) -> Result<Self, AdventError> {
+        let mut parts = line.split_ascii_whitespace();
+        let name = parts.next().unwrap_or_default();
+        let argument = parts.next();
+        if parts.next().is_some() {
+            return Err(AdventError::new("A command had too many arguments"));
+        }
+        match (name, argument) {
+            ("add", Some(range)) => Ok(Self::Add(parse_fresh_range(range, swap_inverted)?)),
+            ("remove", Some(range)) => Ok(Self::Remove(parse_fresh_range(range, swap_inverted)?)),
+            ("query", Some(id)) => {
+                let Ok(id) = id.parse::<u64>() else {
+                    return Err(AdventError::new("The queried ID was not a valid integer"));
+                };
+                Ok(Self::Query(id))
+            }
+            ("count", None) => Ok(Self::Count),
+            _ => Err(AdventError::new("Encountered an unknown command")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::Command;
+
+    #[test]
+    fn test_parse() {
+        let Command::Add(range) = Command::parse("add 3-5", false).unwrap() else {
+            panic!("Expected an add command");
+        };
+        assert_eq!((3, 5), (range.start, range.end));
+        assert!(matches!(
+            Command::parse("remove 4-4", false),
+            Ok(Command::Remove(_))
+        ));
+        assert!(matches!(
+            Command::parse("query 7", false),
+            Ok(Command::Query(7))
+        ));
+        assert!(matches!(Command::parse("count", false), Ok(Command::Count)));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Command::parse("", false).is_err());
+        assert!(Command::parse("add", false).is_err());
+        assert!(Command::parse("add 3", false).is_err());
+        assert!(Command::parse("query x", false).is_err());
+        assert!(Command::parse("count 1", false).is_err());
+        assert!(Command::parse("drop 1-2", false).is_err());
+        assert!(Command::parse("add 5-3", false).is_err());
+        assert!(Command::parse("add 5-3", true).is_ok());
+    }
+}
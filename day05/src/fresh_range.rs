@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 #[derive(Debug, Copy, Clone)]
 pub struct FreshRange {
     pub start: u64,
@@ -11,36 +13,41 @@ impl FreshRange {
         id >= self.start && id <= self.end
     }
 
+    /// Counts in `u128`, since the range `0-18446744073709551615` holds one
+    /// more ID than fits in a `u64`.
     #[must_use]
-    pub fn count(self) -> u64 {
-        self.end - self.start + 1
+    pub fn count(self) -> u128 {
+        u128::from(self.end - self.start) + 1
     }
 
+    /// Merges two ranges if they overlap or, when `merge_adjacent` is set, if
+    /// one ends right before the other starts.
     #[must_use]
-    pub fn try_merge(self, other: Self) -> Option<Self> {
-        if self.start <= other.start {
-            if self.end >= other.end {
-                Some(self)
-            } else if self.end >= other.start {
-                let range = FreshRange {
-                    start: self.start,
-                    end: other.end,
-                };
-                Some(range)
-            } else {
-                None
-            }
-        } else if self.end <= other.end {
-            Some(other)
-        } else if self.start <= other.end {
-            let range = FreshRange {
-                start: other.start,
-                end: self.end,
-            };
-            Some(range)
+    pub fn try_merge(self, other: Self, merge_adjacent: bool) -> Option<Self> {
+        let (first, second) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let reach = if merge_adjacent {
+            first.end.saturating_add(1)
         } else {
-            None
+            first.end
+        };
+        if reach < second.start {
+            return None;
         }
+        let range = FreshRange {
+            start: first.start,
+            end: first.end.max(second.end),
+        };
+        Some(range)
+    }
+}
+
+impl Display for FreshRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
     }
 }
 
@@ -58,7 +65,7 @@ mod tests {
     fn test_try_merge_overlapping_tail_and_head() {
         let range1 = FreshRange { start: 1, end: 5 };
         let range2 = FreshRange { start: 3, end: 10 };
-        let merged = range1.try_merge(range2).unwrap();
+        let merged = range1.try_merge(range2, false).unwrap();
         assert_eq!(1, merged.start);
         assert_eq!(10, merged.end);
     }
@@ -67,7 +74,7 @@ mod tests {
     fn test_try_merge_overlapping_head_and_tail() {
         let range1 = FreshRange { start: 3, end: 10 };
         let range2 = FreshRange { start: 1, end: 5 };
-        let merged = range1.try_merge(range2).unwrap();
+        let merged = range1.try_merge(range2, false).unwrap();
         assert_eq!(1, merged.start);
         assert_eq!(10, merged.end);
     }
@@ -76,7 +83,7 @@ mod tests {
     fn test_try_merge_overlapping_wraps() {
         let range1 = FreshRange { start: 1, end: 10 };
         let range2 = FreshRange { start: 3, end: 8 };
-        let merged = range1.try_merge(range2).unwrap();
+        let merged = range1.try_merge(range2, false).unwrap();
         assert_eq!(1, merged.start);
         assert_eq!(10, merged.end);
     }
@@ -85,7 +92,7 @@ mod tests {
     fn test_try_merge_overlapping_embedded() {
         let range1 = FreshRange { start: 3, end: 8 };
         let range2 = FreshRange { start: 1, end: 10 };
-        let merged = range1.try_merge(range2).unwrap();
+        let merged = range1.try_merge(range2, false).unwrap();
         assert_eq!(1, merged.start);
         assert_eq!(10, merged.end);
     }
@@ -94,6 +101,39 @@ mod tests {
     fn test_try_merge_non_overlapping() {
         let range1 = FreshRange { start: 1, end: 5 };
         let range2 = FreshRange { start: 6, end: 10 };
-        assert!(range1.try_merge(range2).is_none());
+        assert!(range1.try_merge(range2, false).is_none());
+    }
+
+    #[test]
+    fn test_try_merge_adjacent() {
+        let range1 = FreshRange { start: 6, end: 10 };
+        let range2 = FreshRange { start: 1, end: 5 };
+        let merged = range1.try_merge(range2, true).unwrap();
+        assert_eq!(1, merged.start);
+        assert_eq!(10, merged.end);
+    }
+
+    #[test]
+    fn test_try_merge_adjacent_at_u64_max() {
+        let range1 = FreshRange {
+            start: 5,
+            end: u64::MAX,
+        };
+        let range2 = FreshRange {
+            start: u64::MAX,
+            end: u64::MAX,
+        };
+        let merged = range1.try_merge(range2, true).unwrap();
+        assert_eq!(5, merged.start);
+        assert_eq!(u64::MAX, merged.end);
+    }
+
+    #[test]
+    fn test_range_count_full_u64() {
+        let range = FreshRange {
+            start: 0,
+            end: u64::MAX,
+        };
+        assert_eq!(1u128 << 64, range.count());
     }
 }
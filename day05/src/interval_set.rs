@@ -0,0 +1,295 @@
+use crate::fresh_range::FreshRange;
+
+/// Holds disjoint ranges sorted by their start, with overlapping ranges
+/// coalesced together. Adjacent ranges are coalesced too, if requested.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalSet {
+    ranges: Vec<FreshRange>,
+    merge_adjacent: bool,
+}
+
+impl IntervalSet {
+    /// Sorts the ranges by their start and then sweeps through them once,
+    /// merging each range into the previous one whenever they overlap.
+    #[must_use]
+    pub fn from_ranges(ranges: &[FreshRange], merge_adjacent: bool) -> Self {
+        let mut sorted = ranges.to_vec();
+        sorted.sort_unstable_by_key(|range| range.start);
+        let mut merged: Vec<FreshRange> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            if let Some(last) = merged.last_mut()
+                && let Some(merged_range) = last.try_merge(range, merge_adjacent)
+            {
+                *last = merged_range;
+            } else {
+                merged.push(range);
+            }
+        }
+        Self {
+            ranges: merged,
+            merge_adjacent,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn ranges(&self) -> &[FreshRange] {
+        &self.ranges
+    }
+
+    /// Finds the last range starting at or before the ID using a binary
+    /// search, which is the only range that could contain it.
+    #[must_use]
+    pub fn contains(&self, id: u64) -> bool {
+        let index = self.ranges.partition_point(|range| range.start <= id);
+        index > 0 && self.ranges[index - 1].contains(id)
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = self.ranges.clone();
+        ranges.extend_from_slice(&other.ranges);
+        Self::from_ranges(&ranges, self.merge_adjacent)
+    }
+
+    /// Walks both sets in order, keeping the overlap of each pair of ranges
+    /// and advancing whichever range ends first.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut index = 0usize;
+        let mut other_index = 0usize;
+        while let (Some(range), Some(other_range)) =
+            (self.ranges.get(index), other.ranges.get(other_index))
+        {
+            let start = range.start.max(other_range.start);
+            let end = range.end.min(other_range.end);
+            if start <= end {
+                ranges.push(FreshRange { start, end });
+            }
+            if range.end < other_range.end {
+                index += 1;
+            } else {
+                other_index += 1;
+            }
+        }
+        Self {
+            ranges,
+            merge_adjacent: self.merge_adjacent,
+        }
+    }
+
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let everything = FreshRange {
+            start: 0,
+            end: u64::MAX,
+        };
+        self.intersection(&other.complement(everything))
+    }
+
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    /// Finds the gaps between the ranges that fall within the bounds.
+    #[must_use]
+    pub fn complement(&self, bounds: FreshRange) -> Self {
+        let mut ranges = Vec::new();
+        let mut next_start = Some(bounds.start);
+        for range in &self.ranges {
+            let Some(start) = next_start else {
+                break;
+            };
+            if range.end < start {
+                continue;
+            }
+            if range.start > bounds.end {
+                break;
+            }
+            if range.start > start {
+                ranges.push(FreshRange {
+                    start,
+                    end: range.start - 1,
+                });
+            }
+            next_start = range.end.checked_add(1);
+        }
+        if let Some(start) = next_start
+            && start <= bounds.end
+        {
+            ranges.push(FreshRange {
+                start,
+                end: bounds.end,
+            });
+        }
+        Self {
+            ranges,
+            merge_adjacent: self.merge_adjacent,
+        }
+    }
+
+    /// The smallest range covering every range in the set.
+    #[must_use]
+    pub fn bounds(&self) -> Option<FreshRange> {
+        let first = self.ranges.first()?;
+        let last = self.ranges.last()?;
+        let range = FreshRange {
+            start: first.start,
+            end: last.end,
+        };
+        Some(range)
+    }
+
+    /// Counts the IDs covered by any of the ranges.
+    #[must_use]
+    pub fn count(&self) -> u128 {
+        self.ranges().iter().map(|range| range.count()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fresh_range::FreshRange;
+    use crate::interval_set::IntervalSet;
+
+    #[test]
+    fn test_from_ranges_coalesces_overlaps() {
+        let ranges = [
+            FreshRange { start: 16, end: 20 },
+            FreshRange { start: 3, end: 5 },
+            FreshRange { start: 12, end: 18 },
+            FreshRange { start: 10, end: 14 },
+        ];
+        let set = IntervalSet::from_ranges(&ranges, false);
+        assert_eq!(vec![(3, 5), (10, 20)], bounds_of(&set));
+        assert_eq!(14, set.count());
+    }
+
+    #[test]
+    fn test_from_ranges_chains_merges() {
+        // The last range bridges the first two, which the sweep must catch.
+        let ranges = [
+            FreshRange { start: 1, end: 3 },
+            FreshRange { start: 7, end: 9 },
+            FreshRange { start: 2, end: 8 },
+        ];
+        let set = IntervalSet::from_ranges(&ranges, false);
+        assert_eq!(1, set.ranges().len());
+        assert_eq!(9, set.count());
+    }
+
+    #[test]
+    fn test_from_ranges_merges_adjacent() {
+        let ranges = [
+            FreshRange { start: 6, end: 10 },
+            FreshRange { start: 1, end: 5 },
+        ];
+        assert_eq!(2, IntervalSet::from_ranges(&ranges, false).ranges().len());
+        let set = IntervalSet::from_ranges(&ranges, true);
+        assert_eq!(vec![(1, 10)], bounds_of(&set));
+    }
+
+    #[test]
+    fn test_count_full_u64() {
+        let ranges = [
+            FreshRange {
+                start: 0,
+                end: u64::MAX,
+            },
+            FreshRange { start: 5, end: 10 },
+        ];
+        let set = IntervalSet::from_ranges(&ranges, false);
+        assert_eq!(1u128 << 64, set.count());
+    }
+
+    #[test]
+    fn test_union() {
+        let first = create_set(&[(1, 5), (20, 25)]);
+        let second = create_set(&[(4, 10), (30, 30)]);
+        assert_eq!(
+            vec![(1, 10), (20, 25), (30, 30)],
+            bounds_of(&first.union(&second))
+        );
+    }
+
+    #[test]
+    fn test_intersection() {
+        let first = create_set(&[(1, 5), (8, 20)]);
+        let second = create_set(&[(4, 10), (15, 30)]);
+        let intersection = first.intersection(&second);
+        assert_eq!(vec![(4, 5), (8, 10), (15, 20)], bounds_of(&intersection));
+        assert_eq!(11, intersection.count());
+    }
+
+    #[test]
+    fn test_difference() {
+        let first = create_set(&[(1, 10), (20, 25)]);
+        let second = create_set(&[(0, 2), (5, 6), (25, 40)]);
+        assert_eq!(
+            vec![(3, 4), (7, 10), (20, 24)],
+            bounds_of(&first.difference(&second))
+        );
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let first = create_set(&[(1, 10)]);
+        let second = create_set(&[(5, 15)]);
+        assert_eq!(
+            vec![(1, 4), (11, 15)],
+            bounds_of(&first.symmetric_difference(&second))
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        let set = create_set(&[(3, 5), (10, 20)]);
+        let complement = set.complement(FreshRange { start: 0, end: 15 });
+        assert_eq!(vec![(0, 2), (6, 9)], bounds_of(&complement));
+        let complement = set.complement(FreshRange { start: 4, end: 30 });
+        assert_eq!(vec![(6, 9), (21, 30)], bounds_of(&complement));
+    }
+
+    #[test]
+    fn test_complement_at_u64_max() {
+        let set = create_set(&[(0, 0), (u64::MAX - 1, u64::MAX)]);
+        let complement = set.complement(FreshRange {
+            start: 0,
+            end: u64::MAX,
+        });
+        assert_eq!(vec![(1, u64::MAX - 2)], bounds_of(&complement));
+    }
+
+    #[test]
+    fn test_contains() {
+        let ranges = [
+            FreshRange { start: 3, end: 5 },
+            FreshRange { start: 10, end: 20 },
+        ];
+        let set = IntervalSet::from_ranges(&ranges, false);
+        assert!(!set.contains(2));
+        assert!(set.contains(3));
+        assert!(set.contains(5));
+        assert!(!set.contains(6));
+        assert!(set.contains(20));
+        assert!(!set.contains(21));
+        assert!(!IntervalSet::default().contains(0));
+    }
+
+    fn create_set(bounds: &[(u64, u64)]) -> IntervalSet {
+        let ranges: Vec<FreshRange> = bounds
+            .iter()
+            .map(|(start, end)| FreshRange {
+                start: *start,
+                end: *end,
+            })
+            .collect();
+        IntervalSet::from_ranges(&ranges, false)
+    }
+
+    fn bounds_of(set: &IntervalSet) -> Vec<(u64, u64)> {
+        set.ranges().iter().map(|r| (r.start, r.end)).collect()
+    }
+}
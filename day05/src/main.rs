@@ -1,77 +1,267 @@
 mod advent_error;
+mod available_ids;
+mod command;
 mod fresh_range;
+mod interval_set;
+mod range_database;
+mod range_index;
+mod range_options;
+mod stream_options;
 
 use crate::advent_error::AdventError;
+use crate::available_ids::{count_fresh_ids, for_each_available_id};
+use crate::command::Command;
 use crate::fresh_range::FreshRange;
+use crate::interval_set::IntervalSet;
+use crate::range_database::RangeDatabase;
+use crate::range_index::RangeIndex;
+use crate::range_options::RangeOptions;
+use crate::stream_options::StreamOptions;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 fn main() -> Result<(), AdventError> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let options = RangeOptions::extract(&mut args);
+    let stream_options = StreamOptions::extract(&mut args)?;
+    if let [command, first_path, second_path, rest @ ..] = args.as_slice()
+        && command == "compare"
+    {
+        let bounds = match rest {
+            [] => None,
+            [bounds] => Some(parse_fresh_range(bounds, options.swap_inverted)?),
+            _ => {
+                return Err(AdventError::new(
+                    "Usage: compare <first> <second> [start-end]",
+                ));
+            }
+        };
+        return run_comparison(
+            Path::new(first_path),
+            Path::new(second_path),
+            bounds,
+            options,
+        );
+    }
+    if let [command, path, ids @ ..] = args.as_slice()
+        && command == "provenance"
+    {
+        let mut query_ids = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Ok(id) = id.parse::<u64>() else {
+                return Err(AdventError::new("Encountered an invalid ID to query"));
+            };
+            query_ids.push(id);
+        }
+        return run_provenance(Path::new(path), &query_ids, options);
+    }
+    if let [command, rest @ ..] = args.as_slice()
+        && command == "commands"
+    {
+        let path = match rest {
+            [] => None,
+            [path] => Some(Path::new(path)),
+            _ => return Err(AdventError::new("Usage: commands [database]")),
+        };
+        return run_commands(path, options);
+    }
     let path = Path::new("day05/resources/input.txt");
-    run_first_part(path)?;
-    run_second_part(path)?;
+    run_first_part(path, options, stream_options)?;
+    run_second_part(path, options)?;
     Ok(())
 }
 
-fn run_first_part(path: &Path) -> Result<(), AdventError> {
-    let (fresh_ranges, available_ids) = read_ingredients(path, true)?;
-    let fresh_count = count_fresh_ingredients(&fresh_ranges, &available_ids);
+fn run_first_part(
+    path: &Path,
+    options: RangeOptions,
+    stream_options: StreamOptions,
+) -> Result<(), AdventError> {
+    let Ok(file) = File::open(path) else {
+        return Err(AdventError::new("Could not open the input file"));
+    };
+    let reader = BufReader::new(file);
+    let fresh_count = count_fresh_ingredients(reader, options, stream_options)?;
     println!("Part 1 - Found {fresh_count} fresh ingredient(s)");
     Ok(())
 }
 
-fn run_second_part(path: &Path) -> Result<(), AdventError> {
-    let (fresh_ranges, _) = read_ingredients(path, false)?;
+fn run_second_part(path: &Path, options: RangeOptions) -> Result<(), AdventError> {
+    let (fresh_ranges, _) = read_ingredients(path, false, options)?;
     let fresh_count = count_all_fresh_ingredients(&fresh_ranges);
     println!("Part 2 - Found {fresh_count} possible fresh ingredient(s)");
     Ok(())
 }
 
+/// Compares the fresh ranges of two databases. The complement is taken
+/// within the given bounds, or else the bounds of both databases combined.
+fn run_comparison(
+    first_path: &Path,
+    second_path: &Path,
+    bounds: Option<FreshRange>,
+    options: RangeOptions,
+) -> Result<(), AdventError> {
+    let (first_ranges, _) = read_ingredients(first_path, false, options)?;
+    let (second_ranges, _) = read_ingredients(second_path, false, options)?;
+    let first = IntervalSet::from_ranges(&first_ranges, options.merge_adjacent);
+    let second = IntervalSet::from_ranges(&second_ranges, options.merge_adjacent);
+    let union = first.union(&second);
+    print_interval_set("Union", &union);
+    print_interval_set("Intersection", &first.intersection(&second));
+    print_interval_set("First minus second", &first.difference(&second));
+    print_interval_set("Second minus first", &second.difference(&first));
+    print_interval_set("Symmetric difference", &first.symmetric_difference(&second));
+    if let Some(bounds) = bounds.or_else(|| union.bounds()) {
+        print_interval_set(
+            &format!("Complement of union within {bounds}"),
+            &union.complement(bounds),
+        );
+    }
+    Ok(())
+}
+
+/// Lists the ranges covering each queried ID, followed by how many available
+/// IDs each range alone makes fresh.
+fn run_provenance(
+    path: &Path,
+    query_ids: &[u64],
+    options: RangeOptions,
+) -> Result<(), AdventError> {
+    let (fresh_ranges, available_ids) = read_ingredients(path, true, options)?;
+    let index = RangeIndex::new(&fresh_ranges);
+    for id in query_ids {
+        let covering = index.covering(*id);
+        println!("ID {id} - covered by {} range(s)", covering.len());
+        for entry in covering {
+            println!("  line {}: {}", entry.line_number, entry.range);
+        }
+    }
+    for (entry, count) in index.unique_coverage(&available_ids) {
+        let note = if count == 0 {
+            " (candidate for removal)"
+        } else {
+            ""
+        };
+        println!(
+            "Line {}: {} - solely covers {count} available ID(s){note}",
+            entry.line_number, entry.range
+        );
+    }
+    Ok(())
+}
+
+/// Applies commands read from stdin to a range database, optionally seeded
+/// with the ranges and available IDs from a file.
+fn run_commands(path: Option<&Path>, options: RangeOptions) -> Result<(), AdventError> {
+    let mut database = if let Some(path) = path {
+        let (fresh_ranges, available_ids) = read_ingredients(path, true, options)?;
+        let mut database = RangeDatabase::new(available_ids);
+        for fresh_range in fresh_ranges {
+            database.insert(fresh_range);
+        }
+        database
+    } else {
+        RangeDatabase::default()
+    };
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            return Err(AdventError::new("Could not read the next command"));
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Command::parse(&line, options.swap_inverted) {
+            Ok(Command::Add(range)) => database.insert(range),
+            Ok(Command::Remove(range)) => database.remove(range),
+            Ok(Command::Query(id)) => {
+                let status = if database.contains(id) {
+                    "fresh"
+                } else {
+                    "spoiled"
+                };
+                println!("{id} is {status}");
+                continue;
+            }
+            Ok(Command::Count) => {}
+            Err(error) => {
+                println!("Error - {error}");
+                continue;
+            }
+        }
+        println!(
+            "{} ID(s) covered, {} available ID(s) fresh",
+            database.covered_count(),
+            database.fresh_count()
+        );
+    }
+    Ok(())
+}
+
+fn print_interval_set(label: &str, interval_set: &IntervalSet) {
+    let ranges = interval_set.ranges();
+    let count = interval_set.count();
+    println!("{label} - {} range(s) covering {count} ID(s)", ranges.len());
+    for range in ranges {
+        println!("  {range}");
+    }
+}
+
 fn read_ingredients(
     path: &Path,
     include_available_ids: bool,
+    options: RangeOptions,
 ) -> Result<(Vec<FreshRange>, Vec<u64>), AdventError> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
     let reader = BufReader::new(file);
-    read_ingredients_direct(reader, include_available_ids)
+    read_ingredients_direct(reader, include_available_ids, options)
 }
 
 fn read_ingredients_direct<R: BufRead>(
-    reader: R,
+    mut reader: R,
     include_available_ids: bool,
+    options: RangeOptions,
 ) -> Result<(Vec<FreshRange>, Vec<u64>), AdventError> {
-    let mut fresh_ranges = Vec::new();
+    let (fresh_ranges, next_line_number) = read_fresh_ranges_direct(&mut reader, options)?;
     let mut available_ids = Vec::new();
-    let mut is_available_section = false;
-    for line in reader.lines() {
-        let Ok(line) = line else {
-            return Err(AdventError::new("Could not read the next line"));
+    if include_available_ids {
+        for_each_available_id(reader, next_line_number, |id| available_ids.push(id))?;
+    }
+    Ok((fresh_ranges, available_ids))
+}
+
+/// Reads the fresh ranges up to and including the blank line that separates
+/// them from the available IDs, leaving the reader at the first ID. Returns
+/// the line number of the first ID, too.
+fn read_fresh_ranges_direct<R: BufRead>(
+    reader: &mut R,
+    options: RangeOptions,
+) -> Result<(Vec<FreshRange>, usize), AdventError> {
+    let mut fresh_ranges = Vec::new();
+    let mut line_number = 0usize;
+    let mut line = String::new();
+    loop {
+        line_number += 1;
+        line.clear();
+        let Ok(length) = reader.read_line(&mut line) else {
+            return Err(AdventError::new(format!(
+                "Line {line_number}: Could not read the next line"
+            )));
         };
-        let line = line.trim_ascii_end();
-        if is_available_section {
-            let Ok(available_id) = line.parse::<u64>() else {
-                return Err(AdventError::new("Encountered an invalid available ID"));
-            };
-            available_ids.push(available_id);
-        } else {
-            if line.is_empty() {
-                if include_available_ids {
-                    is_available_section = true;
-                    continue;
-                }
-                break;
-            }
-            let fresh_range = parse_fresh_range(line)?;
-            fresh_ranges.push(fresh_range);
+        let trimmed = line.trim_ascii_end();
+        if length == 0 || trimmed.is_empty() {
+            break;
         }
+        let fresh_range = parse_fresh_range(trimmed, options.swap_inverted)
+            .map_err(|error| AdventError::new(format!("Line {line_number}: {error}")))?;
+        fresh_ranges.push(fresh_range);
     }
-    Ok((fresh_ranges, available_ids))
+    Ok((fresh_ranges, line_number + 1))
 }
 
-fn parse_fresh_range(line: &str) -> Result<FreshRange, AdventError> {
+fn parse_fresh_range(line: &str, swap_inverted: bool) -> Result<FreshRange, AdventError> {
     let Some((first, second)) = line.split_once('-') else {
         return Err(AdventError::new("A range did not contain two values"));
     };
@@ -81,89 +271,124 @@ fn parse_fresh_range(line: &str) -> Result<FreshRange, AdventError> {
     let Ok(end) = second.parse::<u64>() else {
         return Err(AdventError::new("The second value was not a valid integer"));
     };
-    let range = FreshRange { start, end };
-    Ok(range)
-}
-
-fn count_fresh_ingredients(fresh_ranges: &[FreshRange], available_ids: &Vec<u64>) -> u64 {
-    let mut fresh_count = 0u64;
-    for available_id in available_ids {
-        for fresh_range in fresh_ranges {
-            if fresh_range.contains(*available_id) {
-                fresh_count += 1;
-                break;
-            }
+    if end < start {
+        if !swap_inverted {
+            return Err(AdventError::new(format!(
+                "The range {line} ends before it starts"
+            )));
         }
+        let range = FreshRange {
+            start: end,
+            end: start,
+        };
+        return Ok(range);
     }
-    fresh_count
+    let range = FreshRange { start, end };
+    Ok(range)
 }
 
-fn count_all_fresh_ingredients(fresh_ranges: &[FreshRange]) -> u64 {
-    let merged_ranges = merge_ranges(fresh_ranges);
-    let mut fresh_count = 0u64;
-    for fresh_range in merged_ranges {
-        fresh_count += fresh_range.count();
-    }
-    fresh_count
+/// Reads the fresh ranges and then streams the available IDs past them, so
+/// the IDs are never held in memory all at once.
+fn count_fresh_ingredients<R: BufRead>(
+    mut reader: R,
+    options: RangeOptions,
+    stream_options: StreamOptions,
+) -> Result<usize, AdventError> {
+    let (fresh_ranges, next_line_number) = read_fresh_ranges_direct(&mut reader, options)?;
+    let interval_set = IntervalSet::from_ranges(&fresh_ranges, options.merge_adjacent);
+    count_fresh_ids(reader, next_line_number, &interval_set, stream_options)
 }
 
-fn merge_ranges(fresh_range: &[FreshRange]) -> Vec<FreshRange> {
-    let mut merged_ranges = Vec::new();
-    let mut removed = vec![false; fresh_range.len()];
-    for (index, range) in fresh_range.iter().enumerate() {
-        if removed[index] {
-            continue;
-        }
-        let mut new_range = *range;
-        loop {
-            // If we ever successfully merge with another range, it's possible
-            // there was a previous range we skipped because our ranges didn't
-            // previously overlap, but now they will. So we start back over at
-            // the beginning and try all over again, repeating as many times as
-            // necessary.
-            let mut merged = false;
-            for other_index in (index + 1)..fresh_range.len() {
-                if removed[other_index] {
-                    continue;
-                }
-                let other_range = fresh_range[other_index];
-                if let Some(merged_range) = new_range.try_merge(other_range) {
-                    new_range = merged_range;
-                    removed[other_index] = true;
-                    merged = true;
-                }
-            }
-            if !merged {
-                break;
-            }
-        }
-        merged_ranges.push(new_range);
-        removed[index] = true;
-    }
-    merged_ranges
+fn count_all_fresh_ingredients(fresh_ranges: &[FreshRange]) -> u128 {
+    IntervalSet::from_ranges(fresh_ranges, false).count()
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::range_options::RangeOptions;
+    use crate::stream_options::StreamOptions;
     use crate::{count_all_fresh_ingredients, count_fresh_ingredients, read_ingredients_direct};
     use std::io::Cursor;
 
     #[test]
     fn test_part1_example() {
         let cursor = create_example_cursor();
-        let (ranges, ids) = read_ingredients_direct(cursor, true).unwrap();
-        let fresh_count = count_fresh_ingredients(&ranges, &ids);
+        let fresh_count =
+            count_fresh_ingredients(cursor, RangeOptions::default(), StreamOptions::default())
+                .unwrap();
         assert_eq!(3, fresh_count);
     }
 
+    #[test]
+    fn test_part1_example_sharded() {
+        let cursor = create_example_cursor();
+        let stream_options = StreamOptions {
+            threads: 3,
+            chunk_size: 2,
+        };
+        let fresh_count =
+            count_fresh_ingredients(cursor, RangeOptions::default(), stream_options).unwrap();
+        assert_eq!(3, fresh_count);
+    }
+
+    #[test]
+    fn test_read_ingredients_collects_ids() {
+        let cursor = create_example_cursor();
+        let (ranges, ids) = read_ingredients_direct(cursor, true, RangeOptions::default()).unwrap();
+        assert_eq!(4, ranges.len());
+        assert_eq!(vec![1, 5, 8, 11, 17, 32], ids);
+    }
+
     #[test]
     fn test_part2_example() {
         let cursor = create_example_cursor();
-        let (ranges, _) = read_ingredients_direct(cursor, false).unwrap();
+        let (ranges, _) = read_ingredients_direct(cursor, false, RangeOptions::default()).unwrap();
         let fresh_count = count_all_fresh_ingredients(&ranges);
         assert_eq!(14, fresh_count);
     }
 
+    #[test]
+    fn test_inverted_range_reports_line() {
+        let cursor = Cursor::new("3-5\n14-10\n");
+        let error = read_ingredients_direct(cursor, false, RangeOptions::default()).unwrap_err();
+        assert_eq!(
+            "Line 2: The range 14-10 ends before it starts",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_available_id_reports_line() {
+        let cursor = Cursor::new("3-5\n\n4\nx\n");
+        let error = read_ingredients_direct(cursor, true, RangeOptions::default()).unwrap_err();
+        assert_eq!(
+            "Line 4: Encountered an invalid available ID",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_inverted_range_swapped() {
+        let cursor = Cursor::new("14-10\n");
+        let options = RangeOptions {
+            swap_inverted: true,
+            ..RangeOptions::default()
+        };
+        let (ranges, _) = read_ingredients_direct(cursor, false, options).unwrap();
+        assert_eq!(10, ranges[0].start);
+        assert_eq!(14, ranges[0].end);
+    }
+
+    #[test]
+    fn test_full_u64_range() {
+        let cursor = Cursor::new("0-18446744073709551615\n5-10\n");
+        let (ranges, _) = read_ingredients_direct(cursor, false, RangeOptions::default()).unwrap();
+        assert_eq!(
+            18_446_744_073_709_551_616,
+            count_all_fresh_ingredients(&ranges)
+        );
+    }
+
     fn create_example_cursor() -> Cursor<&'static str> {
         const EXAMPLE_INPUT: &str = r"3-5
 10-14
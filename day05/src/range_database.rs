@@ -0,0 +1,168 @@
+use crate::fresh_range::FreshRange;
+use std::collections::BTreeMap;
+
+/// Keeps disjoint fresh ranges that can be added and removed one at a time,
+/// updating the covered ID count and the fresh available ID count as it goes
+/// rather than recounting everything.
+#[derive(Debug, Default)]
+pub struct RangeDatabase {
+    ranges: BTreeMap<u64, u64>,
+    available_ids: Vec<u64>,
+    covered_count: u128,
+    fresh_count: usize,
+}
+
+impl RangeDatabase {
+    #[must_use]
+    pub fn new(mut available_ids: Vec<u64>) -> Self {
+        available_ids.sort_unstable();
+        Self {
+            available_ids,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn covered_count(&self) -> u128 {
+        self.covered_count
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn fresh_count(&self) -> usize {
+        self.fresh_count
+    }
+
+    #[must_use]
+    pub fn contains(&self, id: u64) -> bool {
+        self.ranges
+            .range(..=id)
+            .next_back()
+            .is_some_and(|(_, end)| *end >= id)
+    }
+
+    pub fn insert(&mut self, range: FreshRange) {
+        let mut merged = range;
+        self.covered_count += range.count();
+        self.fresh_count += self.count_available_ids(range);
+        for overlapping in self.remove_overlapping(range) {
+            // Anything already covered was counted again above.
+            let overlap = intersect(overlapping, range);
+            self.covered_count -= overlap.count();
+            self.fresh_count -= self.count_available_ids(overlap);
+            merged.start = merged.start.min(overlapping.start);
+            merged.end = merged.end.max(overlapping.end);
+        }
+        self.ranges.insert(merged.start, merged.end);
+    }
+
+    /// Removes the IDs in the range, splitting any stored range that only
+    /// partially overlaps it.
+    pub fn remove(&mut self, range: FreshRange) {
+        for overlapping in self.remove_overlapping(range) {
+            let overlap = intersect(overlapping, range);
+            self.covered_count -= overlap.count();
+            self.fresh_count -= self.count_available_ids(overlap);
+            if overlapping.start < range.start {
+                self.ranges.insert(overlapping.start, range.start - 1);
+            }
+            if overlapping.end > range.end {
+                self.ranges.insert(range.end + 1, overlapping.end);
+            }
+        }
+    }
+
+    fn remove_overlapping(&mut self, range: FreshRange) -> Vec<FreshRange> {
+        let overlapping: Vec<FreshRange> = self
+            .ranges
+            .range(..=range.end)
+            .rev()
+            .take_while(|(_, end)| **end >= range.start)
+            .map(|(start, end)| FreshRange {
+                start: *start,
+                end: *end,
+            })
+            .collect();
+        for stored in &overlapping {
+            self.ranges.remove(&stored.start);
+        }
+        overlapping
+    }
+
+    fn count_available_ids(&self, range: FreshRange) -> usize {
+        let start = self.available_ids.partition_point(|id| *id < range.start);
+        let end = self.available_ids.partition_point(|id| *id <= range.end);
+        end - start
+    }
+}
+
+fn intersect(first: FreshRange, second: FreshRange) -> FreshRange {
+    FreshRange {
+        start: first.start.max(second.start),
+        end: first.end.min(second.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fresh_range::FreshRange;
+    use crate::range_database::RangeDatabase;
+
+    #[test]
+    fn test_insert_matches_example() {
+        let mut database = RangeDatabase::new(vec![1, 5, 8, 11, 17, 32]);
+        database.insert(FreshRange { start: 3, end: 5 });
+        database.insert(FreshRange { start: 10, end: 14 });
+        database.insert(FreshRange { start: 16, end: 20 });
+        database.insert(FreshRange { start: 12, end: 18 });
+        assert_eq!(14, database.covered_count());
+        assert_eq!(3, database.fresh_count());
+    }
+
+    #[test]
+    fn test_insert_bridging_range() {
+        let mut database = RangeDatabase::new(vec![2, 5, 9]);
+        database.insert(FreshRange { start: 1, end: 3 });
+        database.insert(FreshRange { start: 7, end: 9 });
+        database.insert(FreshRange { start: 2, end: 8 });
+        assert_eq!(9, database.covered_count());
+        assert_eq!(3, database.fresh_count());
+        assert!(database.contains(5));
+        assert!(!database.contains(10));
+    }
+
+    #[test]
+    fn test_remove_splits_range() {
+        let mut database = RangeDatabase::new(vec![3, 4, 5, 7]);
+        database.insert(FreshRange { start: 3, end: 7 });
+        database.remove(FreshRange { start: 4, end: 4 });
+        assert_eq!(4, database.covered_count());
+        assert_eq!(3, database.fresh_count());
+        assert!(database.contains(3));
+        assert!(!database.contains(4));
+        assert!(database.contains(5));
+    }
+
+    #[test]
+    fn test_remove_across_ranges() {
+        let mut database = RangeDatabase::new(vec![1, 6, 11, 15]);
+        database.insert(FreshRange { start: 1, end: 5 });
+        database.insert(FreshRange { start: 8, end: 12 });
+        database.insert(FreshRange { start: 14, end: 20 });
+        database.remove(FreshRange { start: 3, end: 15 });
+        assert_eq!(7, database.covered_count());
+        assert_eq!(1, database.fresh_count());
+        assert!(database.contains(2));
+        assert!(!database.contains(9));
+        assert!(database.contains(16));
+    }
+
+    #[test]
+    fn test_remove_missing_range() {
+        let mut database = RangeDatabase::new(Vec::new());
+        database.insert(FreshRange { start: 1, end: 5 });
+        database.remove(FreshRange { start: 6, end: 9 });
+        assert_eq!(5, database.covered_count());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::fresh_range::FreshRange;
+
+#[derive(Debug, Copy, Clone)]
+pub struct IndexedRange {
+    pub range: FreshRange,
+    pub line_number: usize,
+}
+
+/// A static interval tree over the original, unmerged ranges. The ranges are
+/// sorted by their start, and each node of an implicit binary tree over that
+/// order remembers the largest end beneath it, so whole subtrees that end
+/// before an ID can be skipped.
+#[derive(Debug)]
+pub struct RangeIndex {
+    entries: Vec<IndexedRange>,
+    max_ends: Vec<u64>,
+}
+
+impl RangeIndex {
+    /// Ranges are numbered by the line they appear on, which is their
+    /// position plus one, since the ranges come first in the input.
+    #[must_use]
+    pub fn new(ranges: &[FreshRange]) -> Self {
+        let mut entries: Vec<IndexedRange> = ranges
+            .iter()
+            .enumerate()
+            .map(|(index, range)| IndexedRange {
+                range: *range,
+                line_number: index + 1,
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.range.start);
+        let mut index = Self {
+            max_ends: vec![0; entries.len() * 4],
+            entries,
+        };
+        if !index.entries.is_empty() {
+            index.build(1, 0, index.entries.len());
+        }
+        index
+    }
+
+    fn build(&mut self, node: usize, low: usize, high: usize) -> u64 {
+        let max_end = if high - low == 1 {
+            self.entries[low].range.end
+        } else {
+            let middle = low + (high - low) / 2;
+            let left = self.build(node * 2, low, middle);
+            let right = self.build(node * 2 + 1, middle, high);
+            left.max(right)
+        };
+        self.max_ends[node] = max_end;
+        max_end
+    }
+
+    /// Finds every original range containing the ID, ordered by line number.
+    #[must_use]
+    pub fn covering(&self, id: u64) -> Vec<IndexedRange> {
+        let mut covering = Vec::new();
+        // Only ranges starting at or before the ID can contain it.
+        let candidates = self
+            .entries
+            .partition_point(|entry| entry.range.start <= id);
+        if candidates > 0 {
+            self.collect_covering(1, 0, self.entries.len(), candidates, id, &mut covering);
+        }
+        covering.sort_by_key(|entry| entry.line_number);
+        covering
+    }
+
+    fn collect_covering(
+        &self,
+        node: usize,
+        low: usize,
+        high: usize,
+        candidates: usize,
+        id: u64,
+        covering: &mut Vec<IndexedRange>,
+    ) {
+        if low >= candidates || self.max_ends[node] < id {
+            return;
+        }
+        if high - low == 1 {
+            covering.push(self.entries[low]);
+            return;
+        }
+        let middle = low + (high - low) / 2;
+        self.collect_covering(node * 2, low, middle, candidates, id, covering);
+        self.collect_covering(node * 2 + 1, middle, high, candidates, id, covering);
+    }
+
+    /// Counts, for each original range, the available IDs that no other range
+    /// covers. A range responsible for no IDs can be deleted without changing
+    /// which available ingredients are fresh.
+    #[must_use]
+    pub fn unique_coverage(&self, available_ids: &[u64]) -> Vec<(IndexedRange, usize)> {
+        let mut counts = vec![0usize; self.entries.len()];
+        for available_id in available_ids {
+            if let [only] = self.covering(*available_id).as_slice() {
+                counts[only.line_number - 1] += 1;
+            }
+        }
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|entry| entry.line_number);
+        entries.into_iter().zip(counts).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fresh_range::FreshRange;
+    use crate::range_index::RangeIndex;
+
+    #[test]
+    fn test_covering() {
+        let index = RangeIndex::new(&create_ranges());
+        let lines = |id| -> Vec<usize> {
+            index
+                .covering(id)
+                .iter()
+                .map(|entry| entry.line_number)
+                .collect()
+        };
+        assert!(lines(1).is_empty());
+        assert_eq!(vec![1], lines(5));
+        assert!(lines(8).is_empty());
+        assert_eq!(vec![2], lines(11));
+        assert_eq!(vec![2, 4], lines(13));
+        assert_eq!(vec![3, 4], lines(17));
+        assert!(lines(32).is_empty());
+    }
+
+    #[test]
+    fn test_covering_nested_ranges() {
+        let ranges = [
+            FreshRange { start: 1, end: 100 },
+            FreshRange { start: 2, end: 3 },
+            FreshRange { start: 4, end: 50 },
+            FreshRange { start: 5, end: 6 },
+        ];
+        let index = RangeIndex::new(&ranges);
+        let lines: Vec<usize> = index.covering(10).iter().map(|e| e.line_number).collect();
+        assert_eq!(vec![1, 3], lines);
+    }
+
+    #[test]
+    fn test_unique_coverage() {
+        let index = RangeIndex::new(&create_ranges());
+        let coverage = index.unique_coverage(&[1, 5, 8, 11, 17, 32]);
+        let counts: Vec<(usize, usize)> = coverage
+            .iter()
+            .map(|(entry, count)| (entry.line_number, *count))
+            .collect();
+        // 17 is covered twice, so neither range is solely responsible.
+        assert_eq!(vec![(1, 1), (2, 1), (3, 0), (4, 0)], counts);
+    }
+
+    fn create_ranges() -> Vec<FreshRange> {
+        vec![
+            FreshRange { start: 3, end: 5 },
+            FreshRange { start: 10, end: 14 },
+            FreshRange { start: 16, end: 20 },
+            FreshRange { start: 12, end: 18 },
+        ]
+    }
+}
@@ -0,0 +1,46 @@
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RangeOptions {
+    /// Accepts ranges like `5-3` by swapping their bounds instead of
+    /// rejecting them.
+    pub swap_inverted: bool,
+    /// Merges ranges that touch without overlapping, like `1-5` and `6-10`.
+    pub merge_adjacent: bool,
+}
+
+impl RangeOptions {
+    /// Removes any recognized flags from the arguments, leaving the rest.
+    #[must_use]
+    pub fn extract(args: &mut Vec<String>) -> Self {
+        let mut options = Self::default();
+        args.retain(|arg| match arg.as_str() {
+            "--swap-inverted" => {
+                options.swap_inverted = true;
+                false
+            }
+            "--merge-adjacent" => {
+                options.merge_adjacent = true;
+                false
+            }
+            _ => true,
+        });
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::range_options::RangeOptions;
+
+    #[test]
+    fn test_extract() {
+        let mut args = vec![
+            "--merge-adjacent".to_string(),
+            "compare".to_string(),
+            "--swap-inverted".to_string(),
+        ];
+        let options = RangeOptions::extract(&mut args);
+        assert!(options.swap_inverted);
+        assert!(options.merge_adjacent);
+        assert_eq!(vec!["compare".to_string()], args);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::advent_error::AdventError;
+
+#[derive(Debug, Copy, Clone)]
+pub struct StreamOptions {
+    /// The number of threads checking available IDs. With a single thread,
+    /// IDs are checked as they are read.
+    pub threads: usize,
+    /// How many available IDs each thread is handed at once.
+    pub chunk_size: usize,
+}
+
+impl StreamOptions {
+    /// Removes any recognized options from the arguments, leaving the rest.
+    pub fn extract(args: &mut Vec<String>) -> Result<Self, AdventError> {
+        let mut options = Self::default();
+        let mut index = 0usize;
+        while index < args.len() {
+            let target = match args[index].as_str() {
+                "--threads" => &mut options.threads,
+                "--chunk-size" => &mut options.chunk_size,
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+            let value = args.get(index + 1).map(|value| value.parse::<usize>());
+            let Some(Ok(value @ 1..)) = value else {
+                return Err(AdventError::new(format!(
+                    "{} expects a positive number",
+                    args[index]
+                )));
+            };
+            *target = value;
+            args.drain(index..=index + 1);
+        }
+        Ok(options)
+    }
+}
+
+impl Default for StreamOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            chunk_size: 65_536,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream_options::StreamOptions;
+
+    #[test]
+    fn test_extract() {
+        let mut args = vec![
+            "--threads".to_string(),
+            "4".to_string(),
+            "other".to_string(),
+            "--chunk-size".to_string(),
+            "100".to_string(),
+        ];
+        let options = StreamOptions::extract(&mut args).unwrap();
+        assert_eq!(4, options.threads);
+        assert_eq!(100, options.chunk_size);
+        assert_eq!(vec!["other".to_string()], args);
+    }
+
+    #[test]
+    fn test_extract_invalid() {
+        let mut args = vec!["--threads".to_string(), "0".to_string()];
+        assert!(StreamOptions::extract(&mut args).is_err());
+        let mut args = vec!["--chunk-size".to_string()];
+        assert!(StreamOptions::extract(&mut args).is_err());
+    }
+}
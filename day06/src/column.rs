@@ -1,12 +1,16 @@
+use crate::number::Number;
 use crate::operator::Operator;
+use shared::AdventError;
 
+/// A problem's values, read as checked `i64` by default or as `BigInt` so
+/// even the widest numbers are exact.
 #[derive(Debug)]
-pub struct Column {
-    pub values: Vec<i64>,
+pub struct Column<N = i64> {
+    pub values: Vec<N>,
     pub operator: Operator,
 }
 
-impl Column {
+impl<N> Column<N> {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
@@ -15,19 +19,130 @@ impl Column {
             operator: Operator::Add,
         }
     }
+}
 
-    #[must_use]
-    pub fn fold(&self) -> i64 {
-        match self.operator {
-            Operator::Add => self.values.iter().sum::<i64>(),
-            Operator::Multiply => self.values.iter().product::<i64>(),
-        }
+impl<N: Number> Column<N> {
+    /// Applies the operator to the values from top to bottom, so `-` and `/`
+    /// fold left-to-right. Folding as `i64` fails on overflow, while folding
+    /// as `BigInt` gives exact results for columns of any height.
+    pub fn fold(&self) -> Result<N, AdventError> {
+        let mut values = self.values.iter().cloned();
+        let initial = match self.operator {
+            Operator::Add => N::from_i64(0),
+            Operator::Multiply => N::from_i64(1),
+            _ => values
+                .next()
+                .ok_or_else(|| AdventError::new("The column did not contain any values"))?,
+        };
+        values.try_fold(initial, |total, value| self.operator.apply(total, value))
     }
 }
 
-impl Default for Column {
+impl<N> Default for Column<N> {
     #[inline]
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::column::Column;
+    use crate::operator::Operator;
+    use shared::BigInt;
+
+    #[test]
+    fn test_fold_subtract() {
+        let column = create_column(Operator::Subtract, &[20, 5, 3]);
+        assert_eq!(12, column.fold().unwrap());
+    }
+
+    #[test]
+    fn test_fold_divide() {
+        let column = create_column(Operator::Divide, &[100, 5, 2]);
+        assert_eq!(10, column.fold().unwrap());
+    }
+
+    #[test]
+    fn test_fold_divide_by_zero() {
+        let column = create_column(Operator::Divide, &[100, 0]);
+        assert!(column.fold().is_err());
+    }
+
+    #[test]
+    fn test_fold_divide_inexact() {
+        let column = create_column(Operator::Divide, &[10, 3]);
+        let error = column.fold().unwrap_err();
+        assert_eq!("10 is not evenly divisible by 3", error.to_string());
+    }
+
+    #[test]
+    fn test_fold_min_max() {
+        let values = [45, 6, 123];
+        assert_eq!(6, create_column(Operator::Min, &values).fold().unwrap());
+        assert_eq!(123, create_column(Operator::Max, &values).fold().unwrap());
+    }
+
+    #[test]
+    fn test_fold_concatenate() {
+        let column = create_column(Operator::Concatenate, &[12, 0, 345, 10]);
+        assert_eq!(12_034_510, column.fold().unwrap());
+    }
+
+    #[test]
+    fn test_fold_empty() {
+        assert_eq!(0, create_column(Operator::Add, &[]).fold().unwrap());
+        assert!(create_column(Operator::Min, &[]).fold().is_err());
+    }
+
+    #[test]
+    fn test_fold_multiply_overflow() {
+        let column = create_column(Operator::Multiply, &[1_000_000; 4]);
+        assert_eq!(
+            "The result overflowed",
+            column.fold().unwrap_err().to_string()
+        );
+        let column = create_big_column(Operator::Multiply, &[1_000_000; 4]);
+        let expected = format!("1{}", "0".repeat(24));
+        assert_eq!(expected, column.fold().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_fold_big_matches_fold() {
+        let columns = [
+            (Operator::Add, &[1, 2, 3][..]),
+            (Operator::Subtract, &[1, 2, 3][..]),
+            (Operator::Divide, &[-100, 5, 2][..]),
+            (Operator::Min, &[5, -2, 3][..]),
+            (Operator::Concatenate, &[12, 0, 345, 10][..]),
+        ];
+        for (operator, values) in columns {
+            let expected = create_column(operator, values).fold().unwrap().to_string();
+            let column = create_big_column(operator, values);
+            assert_eq!(expected, column.fold().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_fold_big_concatenate() {
+        let column = create_column(Operator::Concatenate, &[i64::MAX, i64::MAX]);
+        assert!(column.fold().is_err());
+        let column = create_big_column(Operator::Concatenate, &[i64::MAX, i64::MAX]);
+        let expected = format!("{}{}", i64::MAX, i64::MAX);
+        assert_eq!(expected, column.fold().unwrap().to_string());
+    }
+
+    fn create_column(operator: Operator, values: &[i64]) -> Column {
+        Column {
+            values: values.to_vec(),
+            operator,
+        }
+    }
+
+    fn create_big_column(operator: Operator, values: &[i64]) -> Column<BigInt> {
+        Column {
+            values: values.iter().copied().map(BigInt::from).collect(),
+            operator,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::number::Number;
+use crate::operator::Operator;
+use shared::AdventError;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::slice::Iter;
+use std::str::Chars;
+
+/// A column's values combined by a template of operators and parentheses,
+/// such as `*+` for `a * b + c` or `*(+)` for `a * (b + c)`. The values fill
+/// the gaps in the template in reading order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expression<N = i64> {
+    Value(N),
+    Binary {
+        operator: Operator,
+        left: Box<Expression<N>>,
+        right: Box<Expression<N>>,
+    },
+}
+
+impl<N: Number> Expression<N> {
+    /// Builds the expression tree for a template. Every operator is left
+    /// associative, and `&` binds tightest, then `*` and `/`, then `+` and
+    /// `-`, then `<` and `>`.
+    pub fn parse(template: &str, values: &[N]) -> Result<Self, AdventError> {
+        let mut parser = Parser {
+            symbols: template.chars().peekable(),
+            values: values.iter(),
+        };
+        let expression = parser.parse_expression(0)?;
+        if parser.symbols.next().is_some() {
+            return Err(AdventError::new(
+                "The expression has an unmatched closing parenthesis",
+            ));
+        }
+        if parser.values.next().is_some() {
+            return Err(AdventError::new(
+                "The column has more values than the expression uses",
+            ));
+        }
+        Ok(expression)
+    }
+
+    /// Evaluates the expression with checked arithmetic. Evaluating as `i64`
+    /// fails on overflow, while evaluating as `BigInt` is always exact.
+    pub fn evaluate(&self) -> Result<N, AdventError> {
+        match self {
+            Self::Value(value) => Ok(value.clone()),
+            Self::Binary {
+                operator,
+                left,
+                right,
+            } => operator.apply(left.evaluate()?, right.evaluate()?),
+        }
+    }
+
+    fn precedence(&self) -> Option<u8> {
+        match self {
+            Self::Value(_) => None,
+            Self::Binary { operator, .. } => Some(precedence(*operator)),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter<'_>, parent: u8, right: bool) -> std::fmt::Result {
+        // Operators are left associative, so an equal operator on the right
+        // needs parentheses to keep its meaning.
+        let needs_parentheses = self
+            .precedence()
+            .is_some_and(|precedence| precedence < parent || (right && precedence == parent));
+        if needs_parentheses {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl<N: Number> Display for Expression<N> {
+    /// Renders the expression in infix with only the parentheses it needs.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{value}"),
+            Self::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let parent = precedence(*operator);
+                left.fmt_operand(f, parent, false)?;
+                write!(f, " {operator} ")?;
+                right.fmt_operand(f, parent, true)
+            }
+        }
+    }
+}
+
+fn precedence(operator: Operator) -> u8 {
+    match operator {
+        Operator::Min | Operator::Max => 1,
+        Operator::Add | Operator::Subtract => 2,
+        Operator::Multiply | Operator::Divide => 3,
+        Operator::Concatenate => 4,
+    }
+}
+
+struct Parser<'a, N> {
+    symbols: Peekable<Chars<'a>>,
+    values: Iter<'a, N>,
+}
+
+impl<N: Number> Parser<'_, N> {
+    /// Parses operators by precedence climbing, stopping at the first
+    /// operator that binds less tightly than `minimum`.
+    fn parse_expression(&mut self, minimum: u8) -> Result<Expression<N>, AdventError> {
+        let mut left = self.parse_operand()?;
+        while let Some(&symbol) = self.symbols.peek() {
+            if symbol == ')' {
+                break;
+            }
+            let Some(operator) = Operator::parse(symbol) else {
+                return Err(AdventError::new(format!(
+                    "Encountered an invalid symbol '{symbol}' in the expression"
+                )));
+            };
+            let precedence = precedence(operator);
+            if precedence < minimum {
+                break;
+            }
+            self.symbols.next();
+            let right = self.parse_expression(precedence + 1)?;
+            left = Expression::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expression<N>, AdventError> {
+        if self.symbols.next_if_eq(&'(').is_some() {
+            let expression = self.parse_expression(0)?;
+            if self.symbols.next_if_eq(&')').is_none() {
+                return Err(AdventError::new(
+                    "The expression has an unclosed parenthesis",
+                ));
+            }
+            return Ok(expression);
+        }
+        let Some(value) = self.values.next() else {
+            return Err(AdventError::new(
+                "The expression needs more values than the column has",
+            ));
+        };
+        Ok(Expression::Value(value.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::Expression;
+    use shared::BigInt;
+
+    #[test]
+    fn test_precedence() {
+        let expression = Expression::<i64>::parse("*+", &[2, 3, 4]).unwrap();
+        assert_eq!(10, expression.evaluate().unwrap());
+        assert_eq!("2 * 3 + 4", expression.to_string());
+
+        let expression = Expression::<i64>::parse("+*", &[2, 3, 4]).unwrap();
+        assert_eq!(14, expression.evaluate().unwrap());
+        assert_eq!("2 + 3 * 4", expression.to_string());
+
+        let expression = Expression::<i64>::parse("+&>", &[1, 2, 3, 50]).unwrap();
+        assert_eq!(50, expression.evaluate().unwrap());
+        assert_eq!("1 + 2 & 3 > 50", expression.to_string());
+    }
+
+    #[test]
+    fn test_parentheses() {
+        let expression = Expression::<i64>::parse("*(+)", &[2, 3, 4]).unwrap();
+        assert_eq!(14, expression.evaluate().unwrap());
+        assert_eq!("2 * (3 + 4)", expression.to_string());
+
+        let expression = Expression::<i64>::parse("-(-)", &[10, 4, 3]).unwrap();
+        assert_eq!(9, expression.evaluate().unwrap());
+        assert_eq!("10 - (4 - 3)", expression.to_string());
+
+        // Redundant parentheses are dropped when rendering
+        let expression = Expression::<i64>::parse("(-)-", &[10, 4, 3]).unwrap();
+        assert_eq!(3, expression.evaluate().unwrap());
+        assert_eq!("10 - 4 - 3", expression.to_string());
+    }
+
+    #[test]
+    fn test_single_value() {
+        let expression = Expression::<i64>::parse("", &[7]).unwrap();
+        assert_eq!(Expression::Value(7), expression);
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let cases = [
+            (
+                "*",
+                &[1][..],
+                "The expression needs more values than the column has",
+            ),
+            (
+                "*",
+                &[1, 2, 3][..],
+                "The column has more values than the expression uses",
+            ),
+            (
+                "*(+",
+                &[1, 2, 3][..],
+                "The expression has an unclosed parenthesis",
+            ),
+            (
+                "*)+",
+                &[1, 2, 3][..],
+                "The expression has an unmatched closing parenthesis",
+            ),
+            (
+                "*x",
+                &[1, 2, 3][..],
+                "Encountered an invalid symbol 'x' in the expression",
+            ),
+        ];
+        for (template, values, message) in cases {
+            let error = Expression::<i64>::parse(template, values).unwrap_err();
+            assert_eq!(message, error.to_string());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let expression = Expression::<i64>::parse("+/", &[1, 2, 0]).unwrap();
+        let error = expression.evaluate().unwrap_err();
+        assert_eq!("Encountered a division by zero", error.to_string());
+    }
+
+    #[test]
+    fn test_evaluate_big() {
+        // The product overflows an i64, but the quotient fits again
+        let values = [4_000_000_000, 6_000_000_000, 8_000_000_000];
+        let expression = Expression::parse("*/", &values).unwrap();
+        assert!(expression.evaluate().is_err());
+        let expression = Expression::parse("*/", &create_big_values(&values)).unwrap();
+        assert_eq!("3000000000", expression.evaluate().unwrap().to_string());
+
+        let values = [i64::MAX, i64::MAX, 2, i64::MAX, 7];
+        let expression = Expression::parse("(**/)&", &values).unwrap();
+        assert_eq!(
+            "The result overflowed",
+            expression.evaluate().unwrap_err().to_string()
+        );
+        let expression = Expression::parse("(**/)&", &create_big_values(&values)).unwrap();
+        let expected = format!("{}7", BigInt::from(i64::MAX).mul_i64(2));
+        assert_eq!(expected, expression.evaluate().unwrap().to_string());
+    }
+
+    fn create_big_values(values: &[i64]) -> Vec<BigInt> {
+        values.iter().copied().map(BigInt::from).collect()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::layout_error::LayoutError;
+use crate::operator::Operator;
+use std::ops::Range;
+
+/// A group of character columns that make up one worksheet problem.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Block {
+    pub columns: Range<usize>,
+    pub operator: Operator,
+}
+
+/// Pads every line with spaces to the length of the longest line, since
+/// editors often strip trailing spaces.
+#[must_use]
+pub fn pad_lines(lines: &[String]) -> Vec<Vec<char>> {
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| {
+            let mut chars: Vec<char> = line.chars().collect();
+            chars.resize(width, ' ');
+            chars
+        })
+        .collect()
+}
+
+/// Splits a padded grid into blocks. Separators are the character columns
+/// that are blank on every line, so gutters can be any width. Each block must
+/// have exactly one operator on the operator line.
+pub fn detect_blocks(grid: &[Vec<char>], operator_line: usize) -> Result<Vec<Block>, LayoutError> {
+    let Some(operators) = grid.get(operator_line) else {
+        return Err(LayoutError::MissingOperatorLine);
+    };
+    block_ranges(grid)
+        .into_iter()
+        .map(|columns| create_block(operators, columns))
+        .collect()
+}
+
+/// Finds the character columns of each block, without looking at what is on
+/// the operator line.
+#[must_use]
+pub fn block_ranges(grid: &[Vec<char>]) -> Vec<Range<usize>> {
+    let width = grid.first().map_or(0, Vec::len);
+    let is_separator = |column: usize| grid.iter().all(|line| line[column] == ' ');
+
+    let mut ranges = Vec::new();
+    let mut column = 0usize;
+    while column < width {
+        if is_separator(column) {
+            column += 1;
+            continue;
+        }
+        let start = column;
+        while column < width && !is_separator(column) {
+            column += 1;
+        }
+        ranges.push(start..column);
+    }
+    ranges
+}
+
+fn create_block(operators: &[char], columns: Range<usize>) -> Result<Block, LayoutError> {
+    let mut symbols = String::new();
+    for column in columns.clone() {
+        let value = operators[column];
+        if value == ' ' {
+            continue;
+        }
+        if Operator::parse(value).is_none() {
+            return Err(LayoutError::InvalidOperator {
+                column: column + 1,
+                value,
+            });
+        }
+        symbols.push(value);
+    }
+    if symbols.is_empty() {
+        return Err(LayoutError::MissingOperator {
+            first_column: columns.start + 1,
+            last_column: columns.end,
+        });
+    }
+    // Every symbol is an operator, so only a lone one parses as a whole
+    let Some(operator) = Operator::parse_str(&symbols) else {
+        return Err(LayoutError::MultipleOperators {
+            first_column: columns.start + 1,
+            last_column: columns.end,
+        });
+    };
+    Ok(Block { columns, operator })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::{Block, detect_blocks, pad_lines};
+    use crate::layout_error::LayoutError;
+    use crate::operator::Operator;
+
+    #[test]
+    fn test_pad_lines() {
+        let lines = ["12".to_string(), "1234".to_string(), String::new()];
+        let grid = pad_lines(&lines);
+        assert!(grid.iter().all(|line| line.len() == 4));
+        assert_eq!(vec!['1', '2', ' ', ' '], grid[0]);
+    }
+
+    #[test]
+    fn test_detect_blocks_with_wide_gutters() {
+        let lines = [
+            "12    3".to_string(),
+            " 4   56".to_string(),
+            "+    *".to_string(),
+        ];
+        let grid = pad_lines(&lines);
+        let blocks = detect_blocks(&grid, 2).unwrap();
+        let expected = vec![
+            Block {
+                columns: 0..2,
+                operator: Operator::Add,
+            },
+            Block {
+                columns: 5..7,
+                operator: Operator::Multiply,
+            },
+        ];
+        assert_eq!(expected, blocks);
+    }
+
+    #[test]
+    fn test_detect_blocks_missing_operator() {
+        let grid = pad_lines(&["1 2".to_string(), "+".to_string()]);
+        let error = detect_blocks(&grid, 1).unwrap_err();
+        assert_eq!(
+            LayoutError::MissingOperator {
+                first_column: 3,
+                last_column: 3
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_detect_blocks_multiple_operators() {
+        let grid = pad_lines(&["12".to_string(), "+*".to_string()]);
+        let error = detect_blocks(&grid, 1).unwrap_err();
+        assert!(matches!(error, LayoutError::MultipleOperators { .. }));
+    }
+
+    #[test]
+    fn test_detect_blocks_invalid_operator() {
+        let grid = pad_lines(&["12".to_string(), "x ".to_string()]);
+        let error = detect_blocks(&grid, 1).unwrap_err();
+        assert_eq!(
+            LayoutError::InvalidOperator {
+                column: 1,
+                value: 'x'
+            },
+            error
+        );
+    }
+}
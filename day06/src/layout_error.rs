@@ -0,0 +1,90 @@
+use shared::AdventError;
+use std::fmt::{Display, Formatter};
+
+/// Describes why a worksheet could not be laid out. Lines and
+/// columns are numbered from 1.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LayoutError {
+    MissingOperatorLine,
+    InvalidOperator {
+        column: usize,
+        value: char,
+    },
+    MissingOperator {
+        first_column: usize,
+        last_column: usize,
+    },
+    MultipleOperators {
+        first_column: usize,
+        last_column: usize,
+    },
+    InvalidDigit {
+        line: usize,
+        column: usize,
+        value: char,
+    },
+    /// The number starting at this line and column does not fit.
+    NumberTooLarge {
+        line: usize,
+        column: usize,
+    },
+    SplitNumber {
+        line: usize,
+        first_column: usize,
+        last_column: usize,
+    },
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingOperatorLine => write!(f, "The worksheet has no operator line"),
+            Self::InvalidOperator { column, value } => {
+                write!(
+                    f,
+                    "Encountered an invalid operator '{value}' in column {column}"
+                )
+            }
+            Self::MissingOperator {
+                first_column,
+                last_column,
+            } => write!(
+                f,
+                "Columns {first_column}-{last_column} do not have an operator"
+            ),
+            Self::MultipleOperators {
+                first_column,
+                last_column,
+            } => write!(
+                f,
+                "Columns {first_column}-{last_column} have more than one operator"
+            ),
+            Self::InvalidDigit {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "Encountered an invalid digit '{value}' on line {line}, column {column}"
+            ),
+            Self::NumberTooLarge { line, column } => write!(
+                f,
+                "The number starting on line {line}, column {column} is too large"
+            ),
+            Self::SplitNumber {
+                line,
+                first_column,
+                last_column,
+            } => write!(
+                f,
+                "Line {line} has more than one number in columns {first_column}-{last_column}"
+            ),
+        }
+    }
+}
+
+impl From<LayoutError> for AdventError {
+    fn from(error: LayoutError) -> Self {
+        AdventError::new(error.to_string())
+    }
+}
@@ -1,186 +1,724 @@
 mod column;
+mod expression;
+mod layout;
+mod layout_error;
+mod number;
 mod operator;
+mod options;
+mod reading_mode;
+mod worksheet_writer;
 
 use crate::column::Column;
-use crate::operator::Operator;
-use shared::AdventError;
+use crate::expression::Expression;
+use crate::layout::{block_ranges, detect_blocks, pad_lines};
+use crate::layout_error::LayoutError;
+use crate::number::Number;
+use crate::options::Options;
+use crate::reading_mode::ReadingMode;
+use crate::worksheet_writer::{WorksheetLayout, write_solved_worksheet, write_worksheet};
+use shared::{AdventError, BigInt};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::ops::Range;
 use std::path::Path;
 
 fn main() -> Result<(), AdventError> {
     let path = Path::new("day06/resources/input.txt");
-    run_first_part(path)?;
-    run_second_part(path)?;
+    let options = Options::parse(std::env::args().skip(1))?;
+    // Numbers are read as checked `i64`, or exactly if `big` is set
+    if options.big {
+        run_first_part::<BigInt>(path, &options)?;
+        run_second_part::<BigInt>(path, &options)?;
+    } else {
+        run_first_part::<i64>(path, &options)?;
+        run_second_part::<i64>(path, &options)?;
+    }
+    Ok(())
+}
+
+fn run_first_part<N: Number>(path: &Path, options: &Options) -> Result<(), AdventError> {
+    if options.expressions {
+        let expressions = read_expressions_direct::<N, _>(open_input(path)?, options.mode)?;
+        return print_expressions("Part 1", &expressions);
+    }
+    let columns = read_columns_direct::<N, _>(open_input(path)?, options.mode)?;
+    print_total("Part 1", &columns)?;
+    if options.solved {
+        let worksheet =
+            write_solved_worksheet(&columns, WorksheetLayout::Horizontal, options.mode)?;
+        print!("{worksheet}");
+    }
     Ok(())
 }
 
-fn run_first_part(path: &Path) -> Result<(), AdventError> {
-    let columns = read_columns(path)?;
-    let total = sum_results(&columns);
-    println!("Part 1 - Total {total}");
+fn print_total<N: Number>(label: &str, columns: &[Column<N>]) -> Result<(), AdventError> {
+    let total = sum_results(columns)?;
+    println!("{label} - Total {total}");
     Ok(())
 }
 
-fn read_columns(path: &Path) -> Result<Vec<Column>, AdventError> {
+fn open_input(path: &Path) -> Result<BufReader<File>, AdventError> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
-    let reader = BufReader::new(file);
-    read_columns_direct(reader)
+    Ok(BufReader::new(file))
 }
 
-fn read_columns_direct<R: BufRead>(reader: R) -> Result<Vec<Column>, AdventError> {
-    let mut columns = Vec::new();
-    let mut is_operators = false;
+fn read_lines<R: BufRead>(reader: R) -> Result<Vec<String>, AdventError> {
+    let mut lines = Vec::new();
     for line in reader.lines() {
         let Ok(line) = line else {
             return Err(AdventError::new("Could not read the next input line"));
         };
-        let values = line.split_ascii_whitespace();
-        for (index, value) in values.enumerate() {
-            if index == columns.len() {
-                columns.push(Column::default());
-            }
-            let column = &mut columns[index];
-            if is_operators {
-                let Some(operator) = Operator::parse_str(value) else {
-                    return Err(AdventError::new("Encountered an invalid operator"));
-                };
-                column.operator = operator;
-            } else if let Ok(number) = value.parse::<i64>() {
-                column.values.push(number);
-            } else if index == 0
-                && let Some(operator) = Operator::parse_str(value)
-            {
-                is_operators = true;
-                column.operator = operator;
-            } else {
-                return Err(AdventError::new(
-                    "Encountered an invalid number or operator",
-                ));
-            }
-        }
+        let line = line.trim_end_matches('\r').to_string();
+        lines.push(line);
     }
-    Ok(columns)
+    // Ignore any blank lines at the end
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    Ok(lines)
 }
 
-fn run_second_part(path: &Path) -> Result<(), AdventError> {
-    let columns = read_columns_hard(path)?;
-    let total = sum_results(&columns);
-    println!("Part 2 - Total {total}");
+fn run_second_part<N: Number>(path: &Path, options: &Options) -> Result<(), AdventError> {
+    if options.expressions {
+        let expressions = read_expressions_hard_direct::<N, _>(open_input(path)?, options.mode)?;
+        return print_expressions("Part 2", &expressions);
+    }
+    let columns = read_columns_hard_direct::<N, _>(open_input(path)?, options.mode)?;
+    print_total("Part 2", &columns)?;
+    if options.solved {
+        let worksheet = write_solved_worksheet(&columns, WorksheetLayout::Vertical, options.mode)?;
+        print!("{worksheet}");
+    }
+    if let Some(layout) = options.write {
+        let worksheet = write_worksheet(&columns, layout, ReadingMode::default())?;
+        print!("{worksheet}");
+    }
     Ok(())
 }
 
-fn sum_results(columns: &Vec<Column>) -> i64 {
-    let mut total = 0i64;
-    for column in columns {
-        let sub_total = column.fold();
-        total += sub_total;
+/// Prints each expression with its result, then the total.
+fn print_expressions<N: Number>(
+    label: &str,
+    expressions: &[Expression<N>],
+) -> Result<(), AdventError> {
+    let results = evaluate_expressions(expressions)?;
+    for (index, (expression, result)) in expressions.iter().zip(&results).enumerate() {
+        println!("{label} - Column {}: {expression} = {result}", index + 1);
     }
-    total
+    let total = sum_values(&results)?;
+    println!("{label} - Total {total}");
+    Ok(())
 }
 
-fn read_columns_hard(path: &Path) -> Result<Vec<Column>, AdventError> {
-    let Ok(file) = File::open(path) else {
-        return Err(AdventError::new("Could not open the input file"));
-    };
-    let reader = BufReader::new(file);
-    read_columns_hard_direct(reader)
+fn evaluate_expressions<N: Number>(expressions: &[Expression<N>]) -> Result<Vec<N>, AdventError> {
+    expressions
+        .iter()
+        .enumerate()
+        .map(|(index, expression)| {
+            expression
+                .evaluate()
+                .map_err(|error| AdventError::new(format!("Column {}: {error}", index + 1)))
+        })
+        .collect()
 }
 
-fn read_columns_hard_direct<R: BufRead>(reader: R) -> Result<Vec<Column>, AdventError> {
-    // Grab all the lines
-    let mut lines = Vec::new();
-    for line in reader.lines() {
-        let Ok(line) = line else {
-            return Err(AdventError::new(""));
+fn sum_values<N: Number>(values: &[N]) -> Result<N, AdventError> {
+    let mut total = N::from_i64(0);
+    for (index, value) in values.iter().enumerate() {
+        let Some(new_total) = total.try_add(value) else {
+            return Err(AdventError::new(format!(
+                "Column {}: The total overflowed",
+                index + 1
+            )));
         };
-        let line = line.trim_end_matches('\r').to_string();
-        lines.push(line);
+        total = new_total;
     }
+    Ok(total)
+}
+
+fn sum_results<N: Number>(columns: &[Column<N>]) -> Result<N, AdventError> {
+    let mut total = N::from_i64(0);
+    for (index, column) in columns.iter().enumerate() {
+        let column_number = index + 1;
+        let sub_total = column
+            .fold()
+            .map_err(|error| AdventError::new(format!("Column {column_number}: {error}")))?;
+        let Some(new_total) = total.try_add(&sub_total) else {
+            return Err(AdventError::new(format!(
+                "Column {column_number}: The total overflowed"
+            )));
+        };
+        total = new_total;
+    }
+    Ok(total)
+}
+
+/// A worksheet padded into a grid of characters, which every reader lays
+/// out the same way.
+struct Worksheet {
+    grid: Vec<Vec<char>>,
+    operator_line: usize,
+    /// The lines holding numbers, from the first to be read to the last.
+    number_lines: Vec<usize>,
+}
+
+/// Reads the values of the problem in the given character columns.
+type ValueReader<N> = fn(&Worksheet, Range<usize>, ReadingMode) -> Result<Vec<N>, LayoutError>;
+
+fn read_worksheet<R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+) -> Result<Option<Worksheet>, AdventError> {
+    let lines = read_lines(reader)?;
+    if lines.is_empty() {
+        return Ok(None);
+    }
+    let grid = pad_lines(&lines);
+    let operator_line = mode.operator_line(grid.len());
+    let mut number_lines: Vec<usize> = (0..grid.len()).filter(|i| *i != operator_line).collect();
+    if mode.bottom_to_top {
+        number_lines.reverse();
+    }
+    Ok(Some(Worksheet {
+        grid,
+        operator_line,
+        number_lines,
+    }))
+}
+
+/// Reads each problem with one number per line, as in part 1.
+fn read_columns_direct<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+) -> Result<Vec<Column<N>>, AdventError> {
+    read_columns(reader, mode, read_horizontal_values)
+}
 
-    // Determine the operators and the indexes of each column
-    let Some(operator_line) = lines.last() else {
+/// Reads each problem with one number per character column, as in part 2.
+fn read_columns_hard_direct<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+) -> Result<Vec<Column<N>>, AdventError> {
+    read_columns(reader, mode, read_vertical_values)
+}
+
+fn read_expressions_direct<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+) -> Result<Vec<Expression<N>>, AdventError> {
+    read_expressions(reader, mode, read_horizontal_values)
+}
+
+fn read_expressions_hard_direct<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+) -> Result<Vec<Expression<N>>, AdventError> {
+    read_expressions(reader, mode, read_vertical_values)
+}
+
+/// Splits the worksheet into problems, each with a single operator, and
+/// reads their values in reading order.
+fn read_columns<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+    read_values: ValueReader<N>,
+) -> Result<Vec<Column<N>>, AdventError> {
+    let Some(worksheet) = read_worksheet(reader, mode)? else {
         return Ok(Vec::new());
     };
-    let mut operators = Vec::new();
-    let mut chunk_ranges = Vec::new();
-    let mut previous_index = 0usize;
-    for (index, value) in operator_line.chars().enumerate() {
-        if let Some(operator) = Operator::parse(value) {
-            operators.push(operator);
-            if index != 0 {
-                let range = previous_index..(index - 1); // Extra blank column
-                chunk_ranges.push(range);
-                previous_index = index;
-            }
-        }
+    let mut blocks = detect_blocks(&worksheet.grid, worksheet.operator_line)?;
+    mode.order(&mut blocks);
+    let mut columns = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let values = read_values(&worksheet, block.columns, mode)?;
+        columns.push(Column {
+            values,
+            operator: block.operator,
+        });
     }
-    chunk_ranges.push(previous_index..operator_line.len());
-    debug_assert!(
-        operators.len() == chunk_ranges.len(),
-        "Each chunk should have an operator"
-    );
-
-    // Break each line into column chunks
-    let mut chunked_lines = Vec::with_capacity(lines.len() - 1);
-    for line in lines.iter().take(lines.len() - 1) {
-        let mut chunked_line = Vec::with_capacity(operators.len());
-        for chunk_range in chunk_ranges.iter().cloned() {
-            let substring = &line[chunk_range];
-            chunked_line.push(substring);
+    Ok(columns)
+}
+
+/// Reads each problem as an expression. The template is every symbol on the
+/// operator line within the problem, always read left to right, so it has
+/// to fit within the problem's character columns.
+fn read_expressions<N: Number, R: BufRead>(
+    reader: R,
+    mode: ReadingMode,
+    read_values: ValueReader<N>,
+) -> Result<Vec<Expression<N>>, AdventError> {
+    let Some(worksheet) = read_worksheet(reader, mode)? else {
+        return Ok(Vec::new());
+    };
+    let mut ranges = block_ranges(&worksheet.grid);
+    mode.order(&mut ranges);
+    let mut expressions = Vec::with_capacity(ranges.len());
+    for (index, columns) in ranges.into_iter().enumerate() {
+        let template: String = worksheet.grid[worksheet.operator_line][columns.clone()]
+            .iter()
+            .filter(|symbol| **symbol != ' ')
+            .collect();
+        let values = read_values(&worksheet, columns, mode)?;
+        let expression = Expression::parse(&template, &values)
+            .map_err(|error| AdventError::new(format!("Column {}: {error}", index + 1)))?;
+        expressions.push(expression);
+    }
+    Ok(expressions)
+}
+
+/// Reads the number on each line within a problem, skipping lines where the
+/// problem is blank.
+fn read_horizontal_values<N: Number>(
+    worksheet: &Worksheet,
+    columns: Range<usize>,
+    _mode: ReadingMode,
+) -> Result<Vec<N>, LayoutError> {
+    let mut values = Vec::with_capacity(worksheet.number_lines.len());
+    for line_index in &worksheet.number_lines {
+        let line = &worksheet.grid[*line_index];
+        if let Some(value) = read_horizontal_number(line, *line_index, &columns)? {
+            values.push(value);
         }
-        chunked_lines.push(chunked_line);
     }
+    Ok(values)
+}
 
-    // Grab the values down each chunk's columns
-    let mut columns = Vec::new();
-    for index in 0..chunk_ranges.len() {
-        let range = chunk_ranges[index].clone();
-        let operator = operators[index];
-        let mut column = Column {
-            operator,
-            ..Column::default()
+/// Reads the digits across a line within a problem, ignoring the padding on
+/// either side. A leading `-` makes the number negative.
+fn read_horizontal_number<N: Number>(
+    line: &[char],
+    line_index: usize,
+    columns: &Range<usize>,
+) -> Result<Option<N>, LayoutError> {
+    let Some(start) = columns.clone().find(|column| line[*column] != ' ') else {
+        return Ok(None);
+    };
+    let end = columns
+        .clone()
+        .rfind(|column| line[*column] != ' ')
+        .map_or(start, |column| column + 1);
+    let negative = line[start] == '-';
+    let digits = if negative { start + 1..end } else { start..end };
+    if digits.is_empty() {
+        return Err(LayoutError::InvalidDigit {
+            line: line_index + 1,
+            column: start + 1,
+            value: '-',
+        });
+    }
+    let mut value = N::from_i64(0);
+    for column_index in digits {
+        let next = line[column_index];
+        if next == ' ' {
+            return Err(LayoutError::SplitNumber {
+                line: line_index + 1,
+                first_column: columns.start + 1,
+                last_column: columns.end,
+            });
+        }
+        let Some(digit) = next.to_digit(10) else {
+            return Err(LayoutError::InvalidDigit {
+                line: line_index + 1,
+                column: column_index + 1,
+                value: next,
+            });
         };
-        column.operator = operator;
-        column.values.resize(range.len(), 0);
-        for line in lines.iter().take(lines.len() - 1) {
-            let chunk: Vec<char> = line[range.clone()].chars().collect();
-            for index in 0..range.len() {
-                let value = &mut column.values[index];
-                let next = chunk[index];
-                if next != ' ' {
-                    let Some(parsed_value) = next.to_digit(10) else {
-                        return Err(AdventError::new("Encountered an invalid number"));
-                    };
-                    *value *= 10;
-                    *value += i64::from(parsed_value);
-                }
-            }
+        // Negative numbers build downwards, so the smallest i64 still fits
+        let Some(next_value) = push_digit(&value, digit, negative) else {
+            return Err(LayoutError::NumberTooLarge {
+                line: line_index + 1,
+                column: start + 1,
+            });
+        };
+        value = next_value;
+    }
+    Ok(Some(value))
+}
+
+/// Reads the digits down each character column within a problem, in reading
+/// order.
+fn read_vertical_values<N: Number>(
+    worksheet: &Worksheet,
+    columns: Range<usize>,
+    mode: ReadingMode,
+) -> Result<Vec<N>, LayoutError> {
+    let mut column_indexes: Vec<usize> = columns.collect();
+    mode.order(&mut column_indexes);
+    let mut values = Vec::with_capacity(column_indexes.len());
+    for column_index in column_indexes {
+        if let Some(value) =
+            read_vertical_number(&worksheet.grid, &worksheet.number_lines, column_index)?
+        {
+            values.push(value);
         }
-        columns.push(column);
     }
-    Ok(columns)
+    Ok(values)
+}
+
+/// Reads the digits in a character column, skipping blanks. A character
+/// column holding only the operator has no number.
+fn read_vertical_number<N: Number>(
+    grid: &[Vec<char>],
+    number_lines: &[usize],
+    column_index: usize,
+) -> Result<Option<N>, LayoutError> {
+    let mut value: Option<N> = None;
+    let mut first_line = None;
+    for line_index in number_lines {
+        let next = grid[*line_index][column_index];
+        if next == ' ' {
+            continue;
+        }
+        let Some(digit) = next.to_digit(10) else {
+            return Err(LayoutError::InvalidDigit {
+                line: line_index + 1,
+                column: column_index + 1,
+                value: next,
+            });
+        };
+        let first_line = *first_line.get_or_insert(*line_index);
+        let value_so_far = value.unwrap_or_else(|| N::from_i64(0));
+        let Some(next_value) = push_digit(&value_so_far, digit, false) else {
+            return Err(LayoutError::NumberTooLarge {
+                line: first_line + 1,
+                column: column_index + 1,
+            });
+        };
+        value = Some(next_value);
+    }
+    Ok(value)
+}
+
+/// Appends a decimal digit to a value, moving away from zero in the
+/// direction of its sign.
+fn push_digit<N: Number>(value: &N, digit: u32, negative: bool) -> Option<N> {
+    let shifted = value.try_mul(&N::from_i64(10))?;
+    let digit = N::from_i64(i64::from(digit));
+    if negative {
+        shifted.try_sub(&digit)
+    } else {
+        shifted.try_add(&digit)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{read_columns_hard_direct, sum_results};
+    use crate::reading_mode::ReadingMode;
+    use crate::{
+        evaluate_expressions, read_columns_direct, read_columns_hard_direct,
+        read_expressions_direct, read_expressions_hard_direct, sum_results, sum_values,
+    };
+    use shared::BigInt;
     use std::io::Cursor;
 
     #[test]
     fn test_part2_example() {
         let cursor = create_cursor();
-        let columns = read_columns_hard_direct(cursor).unwrap();
-        assert_eq!(8_544, columns[0].fold());
-        assert_eq!(625, columns[1].fold());
-        assert_eq!(3_253_600, columns[2].fold());
-        assert_eq!(1_058, columns[3].fold());
-        let total = sum_results(&columns);
+        let columns = read_columns_hard_direct::<i64, _>(cursor, ReadingMode::default()).unwrap();
+        assert_eq!(8_544, columns[0].fold().unwrap());
+        assert_eq!(625, columns[1].fold().unwrap());
+        assert_eq!(3_253_600, columns[2].fold().unwrap());
+        assert_eq!(1_058, columns[3].fold().unwrap());
+        let total = sum_results::<i64>(&columns).unwrap();
         assert_eq!(3_263_827, total);
     }
 
+    #[test]
+    fn test_extended_operators() {
+        let lines = ["20 100 3 7 12", " 5   5 9 2 34", "-  /   < > &"];
+        let cursor = Cursor::new(lines.join("\n"));
+        let columns = read_columns_direct::<i64, _>(cursor, ReadingMode::default()).unwrap();
+        let results: Vec<i64> = columns.iter().map(|c| c.fold().unwrap()).collect();
+        assert_eq!(vec![15, 20, 3, 7, 1_234], results);
+    }
+
+    #[test]
+    fn test_extended_operators_vertical() {
+        let lines = ["8 12", "4  3", "- / "];
+        let cursor = Cursor::new(lines.join("\n"));
+        let columns = read_columns_hard_direct::<i64, _>(cursor, ReadingMode::default()).unwrap();
+        assert_eq!(vec![84], columns[0].values);
+        assert_eq!(vec![1, 23], columns[1].values);
+        let error = sum_results::<i64>(&columns).unwrap_err();
+        assert_eq!(
+            "Column 2: 1 is not evenly divisible by 23",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_overflow_reports_column() {
+        let lines = ["1 3037000500", "2 3037000500", "+ *"].join("\n");
+        let mode = ReadingMode::default();
+        let columns = read_columns_direct::<i64, _>(Cursor::new(&lines), mode).unwrap();
+        let error = sum_results(&columns).unwrap_err();
+        assert_eq!("Column 2: The result overflowed", error.to_string());
+        let columns = read_columns_direct::<BigInt, _>(Cursor::new(&lines), mode).unwrap();
+        let total = sum_results(&columns).unwrap();
+        assert_eq!("9223372037000250003", total.to_string());
+    }
+
+    #[test]
+    fn test_total_overflow_reports_column() {
+        let lines = ["9223372036854775807 1", "+                   +"];
+        let columns =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap();
+        let error = sum_results::<i64>(&columns).unwrap_err();
+        assert_eq!("Column 2: The total overflowed", error.to_string());
+    }
+
+    #[test]
+    fn test_part2_example_stripped_trailing_spaces() {
+        let lines = [
+            "123 328  51 64",
+            " 45 64  387 23",
+            "  6 98  215 314",
+            "*   +   *   +",
+            "",
+        ];
+        let columns = read_columns_hard_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap();
+        let total = sum_results::<i64>(&columns).unwrap();
+        assert_eq!(3_263_827, total);
+    }
+
+    #[test]
+    fn test_part2_wide_gutters() {
+        let lines = ["12    3", " 4   56", "+     *"];
+        let columns = read_columns_hard_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap();
+        assert_eq!(vec![1, 24], columns[0].values);
+        assert_eq!(vec![5, 36], columns[1].values);
+    }
+
+    #[test]
+    fn test_part2_invalid_digit() {
+        let lines = ["12", "4x", "+ "];
+        let error = read_columns_hard_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "Encountered an invalid digit 'x' on line 2, column 2",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_part2_tall_column_big() {
+        // Each character column reads as a 20 digit number, beyond any i64
+        let mut lines = vec!["91"; 20];
+        lines.push("+ ");
+        let lines = lines.join("\n");
+        let mode = ReadingMode::default();
+        let error = read_columns_hard_direct::<i64, _>(Cursor::new(&lines), mode).unwrap_err();
+        assert_eq!(
+            "The number starting on line 1, column 1 is too large",
+            error.to_string()
+        );
+        let columns = read_columns_hard_direct::<BigInt, _>(Cursor::new(&lines), mode).unwrap();
+        assert_eq!("99999999999999999999", columns[0].values[0].to_string());
+        let total = sum_results(&columns).unwrap();
+        assert_eq!("111111111111111111110", total.to_string());
+    }
+
+    #[test]
+    fn test_part1_example() {
+        let columns =
+            read_columns_direct::<i64, _>(create_cursor(), ReadingMode::default()).unwrap();
+        assert_eq!(vec![123, 45, 6], columns[0].values);
+        let total = sum_results::<i64>(&columns).unwrap();
+        assert_eq!(4_277_556, total);
+    }
+
+    #[test]
+    fn test_part1_operators_on_top() {
+        let lines = ["-  *", "10 2", "3  4"];
+        let mode = ReadingMode {
+            operators_on_top: true,
+            right_to_left: true,
+            ..ReadingMode::default()
+        };
+        let columns = read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), mode).unwrap();
+        assert_eq!(vec![2, 4], columns[0].values);
+        assert_eq!(vec![10, 3], columns[1].values);
+    }
+
+    #[test]
+    fn test_part2_right_to_left() {
+        let mode = ReadingMode {
+            right_to_left: true,
+            ..ReadingMode::default()
+        };
+        let columns = read_columns_hard_direct::<i64, _>(create_cursor(), mode).unwrap();
+        assert_eq!(vec![4, 431, 623], columns[0].values);
+        assert_eq!(vec![356, 24, 1], columns[3].values);
+        let total = sum_results::<i64>(&columns).unwrap();
+        assert_eq!(3_263_827, total);
+    }
+
+    #[test]
+    fn test_part2_bottom_to_top_with_operators_on_top() {
+        // The example flipped upside down, read so each number comes out the same.
+        let lines = [
+            "*   +   *   +  ",
+            "  6 98  215 314",
+            " 45 64  387 23 ",
+            "123 328  51 64 ",
+        ];
+        let mode = ReadingMode {
+            bottom_to_top: true,
+            operators_on_top: true,
+            ..ReadingMode::default()
+        };
+        let columns =
+            read_columns_hard_direct::<i64, _>(Cursor::new(lines.join("\n")), mode).unwrap();
+        assert_eq!(vec![1, 24, 356], columns[0].values);
+        let total = sum_results::<i64>(&columns).unwrap();
+        assert_eq!(3_263_827, total);
+    }
+
+    #[test]
+    fn test_expressions() {
+        let lines = ["2  2    10", "3  3    4", "4  4    3", "*+ *(+) -(-)"];
+        let expressions = read_expressions_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap();
+        let rendered: Vec<String> = expressions.iter().map(ToString::to_string).collect();
+        assert_eq!(vec!["2 * 3 + 4", "2 * (3 + 4)", "10 - (4 - 3)"], rendered);
+        let results = evaluate_expressions::<i64>(&expressions).unwrap();
+        assert_eq!(vec![10, 14, 9], results);
+        assert_eq!(33, sum_values(&results).unwrap());
+    }
+
+    #[test]
+    fn test_expressions_vertical() {
+        let lines = ["124 1", "3    5", "*+  -"];
+        let expressions = read_expressions_hard_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap();
+        assert_eq!("13 * 2 + 4", expressions[0].to_string());
+        assert_eq!(
+            vec![30],
+            evaluate_expressions::<i64>(&expressions[..1]).unwrap()
+        );
+        assert_eq!("1 - 5", expressions[1].to_string());
+    }
+
+    #[test]
+    fn test_expressions_report_column() {
+        let lines = ["1 1", "2 2", "+ +*"];
+        let error = read_expressions_direct::<i64, _>(
+            Cursor::new(lines.join("\n")),
+            ReadingMode::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "Column 2: The expression needs more values than the column has",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_expressions_big() {
+        let lines = [
+            "4000000000 1",
+            "6000000000 2",
+            "8000000000 3",
+            "*/         ++",
+        ]
+        .join("\n");
+        let mode = ReadingMode::default();
+        let expressions = read_expressions_direct::<i64, _>(Cursor::new(&lines), mode).unwrap();
+        let error = evaluate_expressions(&expressions).unwrap_err();
+        assert_eq!("Column 1: The result overflowed", error.to_string());
+        let expressions = read_expressions_direct::<BigInt, _>(Cursor::new(&lines), mode).unwrap();
+        let results = evaluate_expressions(&expressions).unwrap();
+        assert_eq!("3000000006", sum_values(&results).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_layout_errors_match() {
+        let worksheets = [["1 2", "+"], ["12", "+*"], ["1x", "+ "], ["12", "x "]];
+        for lines in worksheets {
+            let lines = lines.join("\n");
+            let mode = ReadingMode::default();
+            let horizontal = read_columns_direct::<i64, _>(Cursor::new(&lines), mode).unwrap_err();
+            let vertical =
+                read_columns_hard_direct::<i64, _>(Cursor::new(&lines), mode).unwrap_err();
+            assert_eq!(vertical.to_string(), horizontal.to_string());
+        }
+        let lines = ["1 2", "+"].join("\n");
+        let error =
+            read_columns_direct::<i64, _>(Cursor::new(lines), ReadingMode::default()).unwrap_err();
+        assert_eq!("Columns 3-3 do not have an operator", error.to_string());
+    }
+
+    #[test]
+    fn test_horizontal_needs_aligned_problems() {
+        // Problems are split by blank character columns, not by the spaces
+        // between numbers, so numbers that spill into a gutter join problems.
+        let lines = ["1 2", "33 4", "+ *"];
+        let error =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap_err();
+        assert_eq!("Columns 1-4 have more than one operator", error.to_string());
+        let lines = ["1  2", "33 4", "+  *"];
+        let columns =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap();
+        assert_eq!(vec![1, 33], columns[0].values);
+        assert_eq!(vec![2, 4], columns[1].values);
+    }
+
+    #[test]
+    fn test_horizontal_numbers() {
+        let lines = ["-12  7", "  3 -4", "-    *"];
+        let columns =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap();
+        assert_eq!(vec![-12, 3], columns[0].values);
+        assert_eq!(vec![7, -4], columns[1].values);
+
+        let mode = ReadingMode {
+            bottom_to_top: true,
+            ..ReadingMode::default()
+        };
+        let columns = read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), mode).unwrap();
+        assert_eq!(vec![3, -12], columns[0].values);
+
+        let lines = ["1 2", "345", "+  "];
+        let error =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap_err();
+        assert_eq!(
+            "Line 1 has more than one number in columns 1-3",
+            error.to_string()
+        );
+        let lines = ["1", "9223372036854775808", "+"];
+        let error =
+            read_columns_direct::<i64, _>(Cursor::new(lines.join("\n")), ReadingMode::default())
+                .unwrap_err();
+        assert_eq!(
+            "The number starting on line 2, column 1 is too large",
+            error.to_string()
+        );
+    }
+
     fn create_cursor() -> Cursor<String> {
         let lines = [
             "123 328  51 64 ",
@@ -0,0 +1,73 @@
+use shared::BigInt;
+use std::fmt::Display;
+
+/// The arithmetic needed to read values and combine them with the
+/// operators. Each operation returns `None` if the result cannot be
+/// represented.
+pub trait Number: Sized + Clone + Ord + Display {
+    fn from_i64(value: i64) -> Self;
+
+    fn try_add(&self, other: &Self) -> Option<Self>;
+
+    fn try_sub(&self, other: &Self) -> Option<Self>;
+
+    fn try_mul(&self, other: &Self) -> Option<Self>;
+
+    /// Divides, truncating towards zero, and returns the remainder as well.
+    /// The caller rules out dividing by zero.
+    fn try_div_rem(&self, other: &Self) -> Option<(Self, Self)>;
+}
+
+impl Number for i64 {
+    #[inline]
+    fn from_i64(value: i64) -> Self {
+        value
+    }
+
+    #[inline]
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+
+    #[inline]
+    fn try_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_sub(*other)
+    }
+
+    #[inline]
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(*other)
+    }
+
+    #[inline]
+    fn try_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        Some((self.checked_div(*other)?, self.checked_rem(*other)?))
+    }
+}
+
+impl Number for BigInt {
+    #[inline]
+    fn from_i64(value: i64) -> Self {
+        BigInt::from(value)
+    }
+
+    #[inline]
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    #[inline]
+    fn try_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+
+    #[inline]
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    #[inline]
+    fn try_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        self.div_rem(other)
+    }
+}
@@ -1,15 +1,24 @@
-#[derive(Debug, Copy, Clone)]
+use crate::number::Number;
+use shared::AdventError;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Operator {
     Add,
     Multiply,
+    Subtract,
+    Divide,
+    Min,
+    Max,
+    Concatenate,
 }
 
 impl Operator {
     #[must_use]
     pub fn parse_str(value: &str) -> Option<Operator> {
-        match value {
-            "+" => Some(Operator::Add),
-            "*" => Some(Operator::Multiply),
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(value), None) => Self::parse(value),
             _ => None,
         }
     }
@@ -19,7 +28,86 @@ impl Operator {
         match value {
             '+' => Some(Operator::Add),
             '*' => Some(Operator::Multiply),
+            '-' => Some(Operator::Subtract),
+            '/' => Some(Operator::Divide),
+            '<' => Some(Operator::Min),
+            '>' => Some(Operator::Max),
+            '&' => Some(Operator::Concatenate),
             _ => None,
         }
     }
+
+    /// Combines two values with checked arithmetic, as each step of folding
+    /// a column does. Division has to be exact.
+    pub fn apply<N: Number>(self, left: N, right: N) -> Result<N, AdventError> {
+        let zero = N::from_i64(0);
+        match self {
+            Operator::Add => left.try_add(&right).ok_or_else(overflowed),
+            Operator::Multiply => left.try_mul(&right).ok_or_else(overflowed),
+            Operator::Subtract => left.try_sub(&right).ok_or_else(overflowed),
+            Operator::Divide => {
+                if right == zero {
+                    return Err(AdventError::new("Encountered a division by zero"));
+                }
+                let (quotient, remainder) = left.try_div_rem(&right).ok_or_else(overflowed)?;
+                if remainder != zero {
+                    return Err(AdventError::new(format!(
+                        "{left} is not evenly divisible by {right}"
+                    )));
+                }
+                Ok(quotient)
+            }
+            Operator::Min => Ok(left.min(right)),
+            Operator::Max => Ok(left.max(right)),
+            Operator::Concatenate => {
+                if left < zero || right < zero {
+                    return Err(AdventError::new("Cannot concatenate negative numbers"));
+                }
+                // Shift the left value by one decimal place per digit.
+                let ten = N::from_i64(10);
+                let mut shifted = left;
+                for _ in right.to_string().chars() {
+                    shifted = shifted.try_mul(&ten).ok_or_else(overflowed)?;
+                }
+                shifted.try_add(&right).ok_or_else(overflowed)
+            }
+        }
+    }
+}
+
+fn overflowed() -> AdventError {
+    AdventError::new("The result overflowed")
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Operator::Add => '+',
+            Operator::Multiply => '*',
+            Operator::Subtract => '-',
+            Operator::Divide => '/',
+            Operator::Min => '<',
+            Operator::Max => '>',
+            Operator::Concatenate => '&',
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operator::Operator;
+
+    #[test]
+    fn test_parse_matches_parse_str() {
+        for symbol in ['+', '*', '-', '/', '<', '>', '&'] {
+            let operator = Operator::parse(symbol);
+            assert!(operator.is_some());
+            assert_eq!(operator, Operator::parse_str(&symbol.to_string()));
+            assert_eq!(symbol.to_string(), operator.unwrap().to_string());
+        }
+        assert_eq!(None, Operator::parse('x'));
+        assert_eq!(None, Operator::parse_str("++"));
+        assert_eq!(None, Operator::parse_str(""));
+    }
 }
@@ -0,0 +1,100 @@
+use crate::reading_mode::ReadingMode;
+use crate::worksheet_writer::WorksheetLayout;
+use shared::AdventError;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Totals using arbitrary-precision arithmetic rather than `i64`.
+    pub big: bool,
+    pub mode: ReadingMode,
+    /// Reads each operator line token as an expression template, such as
+    /// `*+` for `a * b + c`.
+    pub expressions: bool,
+    /// Prints each worksheet again with the problem results filled in.
+    pub solved: bool,
+    /// Rewrites the part 2 problems as a worksheet in this layout, read in
+    /// the default direction.
+    pub write: Option<WorksheetLayout>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, AdventError> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--big" => options.big = true,
+                "--right-to-left" => options.mode.right_to_left = true,
+                "--bottom-to-top" => options.mode.bottom_to_top = true,
+                "--operators-on-top" => options.mode.operators_on_top = true,
+                "--expressions" => options.expressions = true,
+                "--solved" => options.solved = true,
+                "--write" => {
+                    let layout = args
+                        .next()
+                        .and_then(|value| WorksheetLayout::parse_str(&value));
+                    let Some(layout) = layout else {
+                        return Err(AdventError::new(
+                            "The --write argument needs a layout of horizontal or vertical",
+                        ));
+                    };
+                    options.write = Some(layout);
+                }
+                _ => return Err(AdventError::new("Encountered an unknown argument")),
+            }
+        }
+        // Worksheets are written from plain columns, which have no template
+        if options.expressions && (options.solved || options.write.is_some()) {
+            return Err(AdventError::new(
+                "The --expressions argument cannot be combined with --solved or --write",
+            ));
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::Options;
+    use crate::worksheet_writer::WorksheetLayout;
+
+    #[test]
+    fn test_parse() {
+        let args = ["--big", "--right-to-left", "--operators-on-top"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert!(options.big);
+        assert!(options.mode.right_to_left);
+        assert!(!options.mode.bottom_to_top);
+        assert!(options.mode.operators_on_top);
+        assert!(!options.solved);
+        assert_eq!(None, options.write);
+    }
+
+    #[test]
+    fn test_parse_worksheet_output() {
+        let args = ["--solved", "--write", "vertical"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert!(options.solved);
+        assert!(!options.expressions);
+        assert_eq!(Some(WorksheetLayout::Vertical), options.write);
+
+        let args = ["--expressions", "--big"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert!(options.expressions);
+        assert!(options.big);
+        let args = ["--solved", "--expressions"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        let args = ["--expressions", "--write", "horizontal"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+
+        let args = ["--write", "diagonal"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        let args = ["--write"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_argument() {
+        let args = ["--sideways"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+    }
+}
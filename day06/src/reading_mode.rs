@@ -0,0 +1,36 @@
+/// Describes the orientation of a worksheet. The default matches the puzzle:
+/// problems are read left to right, the top digit of a vertical number is the
+/// most significant, and the operators are on the last line.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ReadingMode {
+    /// Reads the problems, and the numbers within a vertical problem, from
+    /// right to left.
+    pub right_to_left: bool,
+    /// Reads the number lines from the bottom up, so the bottom digit of a
+    /// vertical number is the most significant, and the bottom horizontal
+    /// number comes first.
+    pub bottom_to_top: bool,
+    /// Expects the operators on the first line instead of the last.
+    pub operators_on_top: bool,
+}
+
+impl ReadingMode {
+    /// Finds the index of the operator line among the given number of lines.
+    #[inline]
+    #[must_use]
+    pub fn operator_line(self, line_count: usize) -> usize {
+        if self.operators_on_top {
+            0
+        } else {
+            line_count.saturating_sub(1)
+        }
+    }
+
+    /// Puts items that were gathered left to right into reading order.
+    #[inline]
+    pub fn order<T>(self, items: &mut [T]) {
+        if self.right_to_left {
+            items.reverse();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::column::Column;
+use crate::number::Number;
+use crate::reading_mode::ReadingMode;
+use shared::AdventError;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WorksheetLayout {
+    /// One number per line in each problem, as read in part 1.
+    Horizontal,
+    /// One number per character column, as read in part 2.
+    Vertical,
+}
+
+impl WorksheetLayout {
+    #[must_use]
+    pub fn parse_str(value: &str) -> Option<Self> {
+        match value {
+            "horizontal" => Some(Self::Horizontal),
+            "vertical" => Some(Self::Vertical),
+            _ => None,
+        }
+    }
+}
+
+/// The lines making up a single problem, before problems are joined.
+#[derive(Debug)]
+struct Block {
+    number_lines: Vec<String>,
+    operator: String,
+    result: Option<String>,
+}
+
+impl Block {
+    fn width(&self) -> usize {
+        let number_width = self.number_lines.iter().map(String::len).max().unwrap_or(0);
+        let result_width = self.result.as_ref().map_or(0, String::len);
+        number_width.max(self.operator.len()).max(result_width)
+    }
+}
+
+/// Writes the columns as a worksheet that reads back as the same columns
+/// using the same layout and reading mode.
+pub fn write_worksheet<N: Number>(
+    columns: &[Column<N>],
+    layout: WorksheetLayout,
+    mode: ReadingMode,
+) -> Result<String, AdventError> {
+    let blocks = create_blocks(columns, layout, mode, false)?;
+    Ok(join_blocks(&blocks, layout, mode))
+}
+
+/// Writes the worksheet with each problem's result on an extra line beyond
+/// the operators.
+pub fn write_solved_worksheet<N: Number>(
+    columns: &[Column<N>],
+    layout: WorksheetLayout,
+    mode: ReadingMode,
+) -> Result<String, AdventError> {
+    let blocks = create_blocks(columns, layout, mode, true)?;
+    Ok(join_blocks(&blocks, layout, mode))
+}
+
+fn create_blocks<N: Number>(
+    columns: &[Column<N>],
+    layout: WorksheetLayout,
+    mode: ReadingMode,
+    solved: bool,
+) -> Result<Vec<Block>, AdventError> {
+    let height = columns.iter().map(|c| c.values.len()).max().unwrap_or(0);
+    let mut blocks = Vec::with_capacity(columns.len());
+    for (index, column) in columns.iter().enumerate() {
+        let result = if solved {
+            let result = column
+                .fold()
+                .map_err(|error| AdventError::new(format!("Column {}: {error}", index + 1)))?;
+            Some(result.to_string())
+        } else {
+            None
+        };
+        let number_lines = match layout {
+            WorksheetLayout::Horizontal => {
+                // Numbers are matched to problems by position, so every
+                // problem needs a number on every line.
+                if column.values.len() != height {
+                    return Err(AdventError::new(
+                        "A horizontal worksheet needs the same number of values in every column",
+                    ));
+                }
+                column.values.iter().map(ToString::to_string).collect()
+            }
+            WorksheetLayout::Vertical => create_vertical_lines(column, mode)?,
+        };
+        blocks.push(Block {
+            number_lines,
+            operator: column.operator.to_string(),
+            result,
+        });
+    }
+    // Blocks are listed in reading order, so flip them back for right to left.
+    mode.order(&mut blocks);
+    Ok(blocks)
+}
+
+/// Writes each value down its own character column, with the most
+/// significant digit on the first line.
+fn create_vertical_lines<N: Number>(
+    column: &Column<N>,
+    mode: ReadingMode,
+) -> Result<Vec<String>, AdventError> {
+    let zero = N::from_i64(0);
+    let mut digits = Vec::with_capacity(column.values.len());
+    for value in &column.values {
+        if *value < zero {
+            return Err(AdventError::new(
+                "A vertical worksheet cannot hold negative numbers",
+            ));
+        }
+        digits.push(value.to_string().into_bytes());
+    }
+    mode.order(&mut digits);
+    let height = digits.iter().map(Vec::len).max().unwrap_or(0);
+    let lines = (0..height)
+        .map(|line| {
+            digits
+                .iter()
+                .map(|digits| digits.get(line).map_or(' ', |d| char::from(*d)))
+                .collect()
+        })
+        .collect();
+    Ok(lines)
+}
+
+fn join_blocks(blocks: &[Block], layout: WorksheetLayout, mode: ReadingMode) -> String {
+    let height = blocks
+        .iter()
+        .map(|b| b.number_lines.len())
+        .max()
+        .unwrap_or(0);
+    let widths: Vec<usize> = blocks.iter().map(Block::width).collect();
+    let join = |cell: &dyn Fn(&Block) -> String, right_align: bool| -> String {
+        let cells: Vec<String> = blocks
+            .iter()
+            .zip(&widths)
+            .map(|(block, width)| {
+                let cell = cell(block);
+                if right_align {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect();
+        cells.join(" ").trim_end().to_string()
+    };
+
+    let right_align = layout == WorksheetLayout::Horizontal;
+    let mut number_lines: Vec<String> = (0..height)
+        .map(|line| {
+            join(
+                &|block| block.number_lines.get(line).cloned().unwrap_or_default(),
+                right_align,
+            )
+        })
+        .collect();
+    if mode.bottom_to_top {
+        number_lines.reverse();
+    }
+    let operator_line = join(&|block| block.operator.clone(), false);
+    let result_line = blocks
+        .iter()
+        .any(|block| block.result.is_some())
+        .then(|| join(&|block| block.result.clone().unwrap_or_default(), false));
+
+    let mut lines = Vec::with_capacity(height + 2);
+    if mode.operators_on_top {
+        lines.extend(result_line);
+        lines.push(operator_line);
+        lines.extend(number_lines);
+    } else {
+        lines.extend(number_lines);
+        lines.push(operator_line);
+        lines.extend(result_line);
+    }
+    let mut worksheet = lines.join("\n");
+    worksheet.push('\n');
+    worksheet
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::column::Column;
+    use crate::operator::Operator;
+    use crate::reading_mode::ReadingMode;
+    use crate::worksheet_writer::{WorksheetLayout, write_solved_worksheet, write_worksheet};
+    use crate::{read_columns_direct, read_columns_hard_direct};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_write_vertical_example() {
+        let worksheet = write_worksheet(
+            &create_columns(),
+            WorksheetLayout::Vertical,
+            ReadingMode::default(),
+        )
+        .unwrap();
+        let expected = "\
+1 3 3 3
+2 2 5 1
+3 9 4 4
+* + * +
+";
+        assert_eq!(expected, worksheet);
+    }
+
+    #[test]
+    fn test_write_horizontal_example() {
+        let columns = [
+            create_column(Operator::Multiply, &[123, 45, 6]),
+            create_column(Operator::Add, &[328, 64, 98]),
+        ];
+        let worksheet = write_worksheet(
+            &columns,
+            WorksheetLayout::Horizontal,
+            ReadingMode::default(),
+        )
+        .unwrap();
+        assert_eq!("123 328\n 45  64\n  6  98\n*   +\n", worksheet);
+    }
+
+    #[test]
+    fn test_round_trip_every_mode() {
+        let columns = [
+            create_column(Operator::Multiply, &[1, 24, 356]),
+            create_column(Operator::Subtract, &[369, 248, 8]),
+            create_column(Operator::Concatenate, &[32, 581, 175]),
+            create_column(Operator::Add, &[623, 431, 4]),
+        ];
+        for bits in 0..8 {
+            let mode = ReadingMode {
+                right_to_left: bits & 1 != 0,
+                bottom_to_top: bits & 2 != 0,
+                operators_on_top: bits & 4 != 0,
+            };
+            let worksheet = write_worksheet(&columns, WorksheetLayout::Vertical, mode).unwrap();
+            let parsed = read_columns_hard_direct::<i64, _>(Cursor::new(&worksheet), mode).unwrap();
+            assert_same_columns(&columns, &parsed);
+
+            let worksheet = write_worksheet(&columns, WorksheetLayout::Horizontal, mode).unwrap();
+            let parsed = read_columns_direct::<i64, _>(Cursor::new(&worksheet), mode).unwrap();
+            assert_same_columns(&columns, &parsed);
+        }
+    }
+
+    #[test]
+    fn test_write_solved() {
+        let columns = [
+            create_column(Operator::Add, &[1, 2]),
+            create_column(Operator::Multiply, &[3, 4]),
+        ];
+        let worksheet = write_solved_worksheet(
+            &columns,
+            WorksheetLayout::Horizontal,
+            ReadingMode::default(),
+        )
+        .unwrap();
+        assert_eq!("1  3\n2  4\n+ *\n3 12\n", worksheet);
+    }
+
+    #[test]
+    fn test_write_invalid() {
+        let ragged = [
+            create_column(Operator::Add, &[1, 2]),
+            create_column(Operator::Add, &[1]),
+        ];
+        let mode = ReadingMode::default();
+        assert!(write_worksheet(&ragged, WorksheetLayout::Horizontal, mode).is_err());
+        assert!(write_worksheet(&ragged, WorksheetLayout::Vertical, mode).is_ok());
+        let negative = [create_column(Operator::Add, &[-1])];
+        assert!(write_worksheet(&negative, WorksheetLayout::Vertical, mode).is_err());
+    }
+
+    fn assert_same_columns(expected: &[Column], actual: &[Column]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert_eq!(expected.operator, actual.operator);
+            assert_eq!(expected.values, actual.values);
+        }
+    }
+
+    fn create_columns() -> Vec<Column> {
+        vec![
+            create_column(Operator::Multiply, &[123]),
+            create_column(Operator::Add, &[329]),
+            create_column(Operator::Multiply, &[354]),
+            create_column(Operator::Add, &[314]),
+        ]
+    }
+
+    fn create_column(operator: Operator, values: &[i64]) -> Column {
+        Column {
+            values: values.to_vec(),
+            operator,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::source::Source;
+use crate::state::{Propagation, State, offset_index};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// The cells classical beams pass through, following each beam down from
+/// its source. A beam leaving a splitter or deflector is drawn beside it on
+/// the same line before it carries on down.
+#[derive(Debug)]
+pub struct BeamTrace {
+    pub cells: Vec<Vec<bool>>,
+    pub splits: u32,
+    /// How many beams end, whether absorbed, off a side of the grid or out of
+    /// the last line. Beams that merge end once.
+    pub ends: u32,
+}
+
+/// A cell where a drawn beam does not match the simulation. Lines and
+/// columns are numbered from 1.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Disagreement {
+    pub line: usize,
+    pub column: usize,
+    pub expected_beam: bool,
+}
+
+impl BeamTrace {
+    /// Traces the beams from every source in the grid.
+    #[must_use]
+    pub fn new(lines: &[Vec<State>]) -> Self {
+        Self::from_sources(lines, &Source::find_all(lines))
+    }
+
+    #[must_use]
+    pub fn from_sources(lines: &[Vec<State>], sources: &[Source]) -> Self {
+        let mut cells: Vec<Vec<bool>> = lines.iter().map(|line| vec![false; line.len()]).collect();
+        let mut splits = 0u32;
+        let mut ends = 0u32;
+        let source_columns = Source::columns_by_line(sources, lines.len());
+        // Tracks which columns have a beam entering the next line
+        let mut beams: Vec<bool> = Vec::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            let mut next_beams = vec![false; line.len()];
+            let mut send = |from: usize, ordering: Ordering, offset: usize| {
+                let Some(index) = offset_index(from, ordering, offset, line.len()) else {
+                    return false;
+                };
+                next_beams[index] = true;
+                cells[line_index][index] = true;
+                true
+            };
+            for (state_index, state) in line.iter().enumerate() {
+                if !beams.get(state_index).copied().unwrap_or(false) {
+                    continue;
+                }
+                match state.propagation() {
+                    Propagation::Straight => {
+                        send(state_index, Ordering::Equal, 0);
+                    }
+                    Propagation::Deflect(ordering) => {
+                        if !send(state_index, ordering, 1) {
+                            ends += 1;
+                        }
+                    }
+                    Propagation::Split(offset) => {
+                        let left = send(state_index, Ordering::Less, offset);
+                        let right = send(state_index, Ordering::Greater, offset);
+                        if left || right {
+                            splits += 1;
+                        }
+                        ends += u32::from(!left) + u32::from(!right);
+                    }
+                    Propagation::Absorb => ends += 1,
+                }
+            }
+            for column in &source_columns[line_index] {
+                next_beams[*column] = true;
+            }
+            beams = next_beams;
+        }
+        ends += u32::try_from(beams.iter().filter(|beam| **beam).count()).unwrap_or(u32::MAX);
+        Self {
+            cells,
+            splits,
+            ends,
+        }
+    }
+
+    /// Compares the beams drawn in the lines, which should have been parsed
+    /// allowing beams, against the simulation. Only empty cells can hold a
+    /// beam, so other tiles never disagree.
+    #[must_use]
+    pub fn validate(&self, lines: &[Vec<State>]) -> Vec<Disagreement> {
+        let mut disagreements = Vec::new();
+        for (line_index, (line, cells)) in lines.iter().zip(&self.cells).enumerate() {
+            for (column_index, (state, traced)) in line.iter().zip(cells).enumerate() {
+                let drawn = match state {
+                    State::Beam => true,
+                    State::Space => false,
+                    _ => continue,
+                };
+                if drawn != *traced {
+                    disagreements.push(Disagreement {
+                        line: line_index + 1,
+                        column: column_index + 1,
+                        expected_beam: *traced,
+                    });
+                }
+            }
+        }
+        disagreements
+    }
+
+    /// Draws the lines with a `|` in every empty cell a beam passes through.
+    #[must_use]
+    pub fn render(&self, lines: &[Vec<State>]) -> String {
+        let mut rendered = String::new();
+        for (line, cells) in lines.iter().zip(&self.cells) {
+            for (state, traced) in line.iter().zip(cells) {
+                let state = match state {
+                    State::Space | State::Beam if *traced => State::Beam,
+                    State::Beam => State::Space,
+                    _ => *state,
+                };
+                rendered.push(state.to_char());
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+impl Display for Disagreement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.expected_beam {
+            write!(
+                f,
+                "Line {}, column {}: A beam passes through but is not drawn",
+                self.line, self.column
+            )
+        } else {
+            write!(
+                f,
+                "Line {}, column {}: A beam is drawn but none reaches it",
+                self.line, self.column
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::beam_trace::{BeamTrace, Disagreement};
+    use crate::parse_traced_lines;
+    use std::io::Cursor;
+
+    const TRACED: [&str; 6] = [
+        "...S...", "...|...", "..|^|..", "..|.|..", ".|^|^|.", ".|.|.|.",
+    ];
+
+    #[test]
+    fn test_render() {
+        let untraced: Vec<String> = TRACED.iter().map(|line| line.replace('|', ".")).collect();
+        let lines = parse_traced_lines(Cursor::new(untraced.join("\n"))).unwrap();
+        let trace = BeamTrace::new(&lines);
+        assert_eq!(3, trace.splits);
+        assert_eq!(TRACED.join("\n") + "\n", trace.render(&lines));
+    }
+
+    #[test]
+    fn test_validate() {
+        let lines = parse_traced_lines(Cursor::new(TRACED.join("\n"))).unwrap();
+        assert!(BeamTrace::new(&lines).validate(&lines).is_empty());
+
+        let mut wrong = TRACED;
+        wrong[3] = "|.|....";
+        let lines = parse_traced_lines(Cursor::new(wrong.join("\n"))).unwrap();
+        let disagreements = BeamTrace::new(&lines).validate(&lines);
+        let expected = vec![
+            Disagreement {
+                line: 4,
+                column: 1,
+                expected_beam: false,
+            },
+            Disagreement {
+                line: 4,
+                column: 5,
+                expected_beam: true,
+            },
+        ];
+        assert_eq!(expected, disagreements);
+        assert_eq!(
+            "Line 4, column 5: A beam passes through but is not drawn",
+            disagreements[1].to_string()
+        );
+    }
+}
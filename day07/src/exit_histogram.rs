@@ -0,0 +1,122 @@
+use shared::BigInt;
+use std::fmt::Write;
+
+/// Where every timeline's beam ends up: out of the bottom line in one of the
+/// columns, off either side of the grid, or stopped by an absorber.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExitHistogram {
+    pub columns: Vec<BigInt>,
+    pub left_edge: BigInt,
+    pub right_edge: BigInt,
+    pub absorbed: BigInt,
+}
+
+impl ExitHistogram {
+    #[must_use]
+    pub fn new(width: usize) -> Self {
+        Self {
+            columns: vec![BigInt::zero(); width],
+            left_edge: BigInt::zero(),
+            right_edge: BigInt::zero(),
+            absorbed: BigInt::zero(),
+        }
+    }
+
+    #[must_use]
+    pub fn total(&self) -> BigInt {
+        let mut total = BigInt::zero();
+        for count in self.rows().iter().map(|(_, count)| count) {
+            total += count;
+        }
+        total
+    }
+
+    /// Lists each exit with its count. Columns no timeline leaves from are
+    /// left out, but the edges and absorbers are always included.
+    fn rows(&self) -> Vec<(String, &BigInt)> {
+        let mut rows = vec![("left edge".to_string(), &self.left_edge)];
+        for (index, count) in self.columns.iter().enumerate() {
+            if !count.is_zero() {
+                rows.push((format!("column {index}"), count));
+            }
+        }
+        rows.push(("right edge".to_string(), &self.right_edge));
+        rows.push(("absorbed".to_string(), &self.absorbed));
+        rows
+    }
+
+    #[must_use]
+    pub fn table(&self) -> String {
+        let rows = self.rows();
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let mut table = String::new();
+        for (label, count) in rows {
+            let _ = writeln!(table, "{label:<label_width$} | {count}");
+        }
+        table
+    }
+
+    /// Draws one bar per exit, scaled so the most common exit fills `width`
+    /// characters. Any exit with a timeline gets at least one character.
+    #[must_use]
+    pub fn bar_chart(&self, width: usize) -> String {
+        let rows = self.rows();
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let largest = rows
+            .iter()
+            .map(|(_, count)| count.to_f64())
+            .fold(0f64, f64::max);
+        let mut chart = String::new();
+        for (label, count) in rows {
+            let length = if count.is_zero() {
+                0
+            } else {
+                bar_length(count.to_f64() / largest, width).max(1)
+            };
+            let bar = "#".repeat(length);
+            let _ = writeln!(chart, "{label:<label_width$} | {bar} {count}");
+        }
+        chart
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn bar_length(fraction: f64, width: usize) -> usize {
+    // A bar is at most a screen wide, so the width is exact as an f64
+    #[allow(clippy::cast_precision_loss)]
+    let width = width as f64;
+    (fraction * width).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exit_histogram::ExitHistogram;
+    use shared::BigInt;
+
+    #[test]
+    fn test_table_and_bar_chart() {
+        let mut histogram = ExitHistogram::new(3);
+        histogram.columns[0] = BigInt::from(10u64);
+        histogram.columns[2] = BigInt::from(1u64);
+        histogram.right_edge = BigInt::from(5u64);
+        assert_eq!(BigInt::from(16u64), histogram.total());
+
+        let expected = "\
+left edge  | 0
+column 0   | 10
+column 2   | 1
+right edge | 5
+absorbed   | 0
+";
+        assert_eq!(expected, histogram.table());
+
+        let expected = "\
+left edge  |  0
+column 0   | ########## 10
+column 2   | # 1
+right edge | ##### 5
+absorbed   |  0
+";
+        assert_eq!(expected, histogram.bar_chart(10));
+    }
+}
@@ -1,21 +1,33 @@
-use crate::state::State;
-use shared::{AdventError, Result};
+use crate::beam_trace::BeamTrace;
+use crate::exit_histogram::ExitHistogram;
+use crate::options::{Options, TimelineCounter};
+use crate::source::{Semantics, Source, SourceTotals};
+use crate::state::{Propagation, State, offset_index};
+use shared::{AdventError, BigInt, Result};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+mod beam_trace;
+mod exit_histogram;
+mod options;
+mod source;
 mod state;
 
 fn main() -> Result<()> {
     let path = Path::new("day07/resources/input.txt");
-    run_part_1(path)?;
-    run_part_2(path)?;
+    let options = Options::parse(std::env::args().skip(1))?;
+    if options.validate {
+        return run_validation(path);
+    }
+    run_part_1(path, &options)?;
+    run_part_2(path, &options)?;
     Ok(())
 }
 
-fn run_part_1(path: &Path) -> Result<()> {
+fn run_part_1(path: &Path, options: &Options) -> Result<()> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
@@ -23,81 +35,154 @@ fn run_part_1(path: &Path) -> Result<()> {
     let lines = parse_lines(reader)?;
     let total_splits = count_splits(&lines);
     println!("Part 1 - Total Splits: {total_splits}");
+    if options.render {
+        print!("{}", BeamTrace::new(&lines).render(&lines));
+    }
     Ok(())
 }
 
-fn count_splits(lines: &[Vec<State>]) -> u32 {
-    let Some(mut current_line) = lines.first().cloned() else {
-        return 0;
+fn run_validation(path: &Path) -> Result<()> {
+    let Ok(file) = File::open(path) else {
+        return Err(AdventError::new("Could not open the input file"));
     };
-    let mut total_splits = 0u32;
-    for next_index in 1..lines.len() {
-        let mut next_line: Vec<State> = lines[next_index].clone();
-        for state_index in 0..current_line.len() {
-            let state = current_line[state_index];
-            match state {
-                State::Beam | State::Start => {
-                    let next_state = &mut next_line[state_index];
-                    match *next_state {
-                        State::Space => *next_state = State::Beam,
-                        State::Splitter => {
-                            let mut split = false;
-                            if state_index > 0 {
-                                let next_left_state = &mut next_line[state_index - 1];
-                                if *next_left_state == State::Space {
-                                    *next_left_state = State::Beam;
-                                }
-                                split = true;
-                            }
-                            if state_index + 1 < next_line.len() {
-                                let next_right_state = &mut next_line[state_index + 1];
-                                if *next_right_state == State::Space {
-                                    *next_right_state = State::Beam;
-                                }
-                                split = true;
-                            }
-                            if split {
-                                total_splits += 1;
-                            }
-                        }
-                        State::Start | State::Beam => {}
-                    }
-                }
-                State::Splitter | State::Space => {}
-            }
-        }
-        current_line = next_line;
+    let reader = BufReader::new(file);
+    let lines = parse_traced_lines(reader)?;
+    let disagreements = BeamTrace::new(&lines).validate(&lines);
+    if disagreements.is_empty() {
+        println!("The drawn beams match the simulation");
     }
-    total_splits
+    for disagreement in &disagreements {
+        println!("{disagreement}");
+    }
+    Ok(())
 }
 
-fn run_part_2(path: &Path) -> Result<()> {
+fn count_splits(lines: &[Vec<State>]) -> u32 {
+    BeamTrace::new(lines).splits
+}
+
+fn run_part_2(path: &Path, options: &Options) -> Result<()> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
     let reader = BufReader::new(file);
     let lines = parse_lines(reader)?;
-    let total_timelines = count_timelines(&lines);
-    println!("Part 2 - Total timelines: {total_timelines}");
+    if options.counter == TimelineCounter::Recursive {
+        let total_timelines = count_timelines(&lines);
+        println!("Part 2 - Total timelines: {total_timelines}");
+    } else {
+        let histogram = trace_timelines(&lines);
+        let total_timelines = histogram.total();
+        println!("Part 2 - Total timelines: {total_timelines}");
+        if options.histogram {
+            print!("{}", histogram.table());
+            print!("{}", histogram.bar_chart(60));
+        }
+    }
+    if let Some(semantics) = options.sources {
+        print_source_report(&lines, semantics);
+    }
     Ok(())
 }
 
+/// Follows timelines a line at a time, carrying how many timelines have a
+/// beam in each column, and records where each one ends. This never
+/// recurses, so any height of manifold is fine, and the counts cannot
+/// overflow.
+fn trace_timelines(lines: &[Vec<State>]) -> ExitHistogram {
+    trace_timelines_from(lines, &Source::find_all(lines))
+}
+
+/// Follows the timelines from the given sources, where timelines from
+/// different sources add together.
+fn trace_timelines_from(lines: &[Vec<State>], sources: &[Source]) -> ExitHistogram {
+    let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+    let mut histogram = ExitHistogram::new(width);
+    let source_columns = Source::columns_by_line(sources, lines.len());
+    let mut counts = vec![BigInt::zero(); width];
+    for (line, columns) in lines.iter().zip(&source_columns) {
+        let mut next_counts = vec![BigInt::zero(); width];
+        for (state_index, count) in counts.iter().enumerate() {
+            if count.is_zero() {
+                continue;
+            }
+            // Columns past the end of a short line are empty space
+            let state = line.get(state_index).copied().unwrap_or(State::Space);
+            let mut send = |ordering: Ordering, offset: usize| match offset_index(
+                state_index,
+                ordering,
+                offset,
+                line.len(),
+            ) {
+                Some(index) => next_counts[index] += count,
+                None if ordering == Ordering::Less => histogram.left_edge += count,
+                None => histogram.right_edge += count,
+            };
+            match state.propagation() {
+                Propagation::Straight => next_counts[state_index] += count,
+                Propagation::Deflect(ordering) => send(ordering, 1),
+                Propagation::Split(offset) => {
+                    send(Ordering::Less, offset);
+                    send(Ordering::Greater, offset);
+                }
+                Propagation::Absorb => histogram.absorbed += count,
+            }
+        }
+        for column in columns {
+            next_counts[*column] += &BigInt::from(1u64);
+        }
+        counts = next_counts;
+    }
+    histogram.columns = counts;
+    histogram
+}
+
 fn count_timelines(lines: &Vec<Vec<State>>) -> u64 {
-    let Some(current_line) = lines.first() else {
-        return 0;
-    };
-    let beam_index = current_line
-        .iter()
-        .copied()
-        .enumerate()
-        .filter(|(_, s)| *s == State::Start)
-        .map(|(ix, _)| ix)
-        .next();
-    let Some(beam_index) = beam_index else {
-        return 0;
-    };
     let mut cache: HashMap<(usize, usize), u64> = HashMap::new();
-    count_alternate_timeline_splits(lines, 1, beam_index, &mut cache) + 1 // Include initial timeline!
+    let mut total = 0;
+    for source in Source::find_all(lines) {
+        // Include the source's initial timeline!
+        total +=
+            count_alternate_timeline_splits(lines, source.line + 1, source.column, &mut cache) + 1;
+    }
+    total
+}
+
+/// Works out the splits and timelines from the sources under the given
+/// semantics. Every quantum split adds exactly one timeline.
+fn source_totals(lines: &[Vec<State>], sources: &[Source], semantics: Semantics) -> SourceTotals {
+    match semantics {
+        Semantics::Classical => {
+            let trace = BeamTrace::from_sources(lines, sources);
+            SourceTotals {
+                splits: BigInt::from(u64::from(trace.splits)),
+                timelines: BigInt::from(u64::from(trace.ends)),
+            }
+        }
+        Semantics::Quantum => {
+            let timelines = trace_timelines_from(lines, sources).total();
+            let splits = &timelines - &BigInt::from(sources.len() as u64);
+            SourceTotals { splits, timelines }
+        }
+    }
+}
+
+fn print_source_report(lines: &[Vec<State>], semantics: Semantics) {
+    let sources = Source::find_all(lines);
+    for source in &sources {
+        let totals = source_totals(lines, std::slice::from_ref(source), semantics);
+        println!(
+            "Source at {source} - Splits: {}, Timelines: {}",
+            totals.splits, totals.timelines
+        );
+    }
+    let totals = source_totals(lines, &sources, semantics);
+    println!(
+        "All {} sources - Splits: {}, Timelines: {}",
+        sources.len(),
+        totals.splits,
+        totals.timelines
+    );
 }
 
 fn count_alternate_timeline_splits(
@@ -113,24 +198,34 @@ fn count_alternate_timeline_splits(
         return *total;
     }
     let state = current_line[beam_index];
-    let count = match state {
-        State::Space => {
-            count_alternate_timeline_splits(lines, current_index + 1, beam_index, cache)
+    let next_index = current_index + 1;
+    let count = match state.propagation() {
+        Propagation::Straight => {
+            count_alternate_timeline_splits(lines, next_index, beam_index, cache)
+        }
+        Propagation::Deflect(ordering) => {
+            count_propagated_beam_paths(lines, next_index, beam_index, ordering, 1, cache)
         }
-        State::Splitter => {
-            let next_index = current_index + 1;
-            let left =
-                count_propagated_beam_paths(lines, next_index, beam_index, Ordering::Less, cache);
+        Propagation::Split(offset) => {
+            let left = count_propagated_beam_paths(
+                lines,
+                next_index,
+                beam_index,
+                Ordering::Less,
+                offset,
+                cache,
+            );
             let right = count_propagated_beam_paths(
                 lines,
                 next_index,
                 beam_index,
                 Ordering::Greater,
+                offset,
                 cache,
             );
             left + right + 1
         }
-        State::Beam | State::Start => 0,
+        Propagation::Absorb => 0,
     };
     cache.insert((current_index, beam_index), count);
     count
@@ -141,31 +236,28 @@ fn count_propagated_beam_paths(
     next_index: usize,
     state_index: usize,
     ordering: Ordering,
+    offset: usize,
     cache: &mut HashMap<(usize, usize), u64>,
 ) -> u64 {
     let Some(next_line) = lines.get(next_index) else {
         return 0;
     };
-    let beam_index = match ordering {
-        Ordering::Equal => state_index,
-        Ordering::Less => {
-            if state_index == 0 {
-                return 0;
-            }
-            state_index - 1
-        }
-        Ordering::Greater => {
-            let right_index = state_index + 1;
-            if right_index >= next_line.len() {
-                return 0;
-            }
-            right_index
-        }
+    let Some(beam_index) = offset_index(state_index, ordering, offset, next_line.len()) else {
+        return 0;
     };
     count_alternate_timeline_splits(lines, next_index, beam_index, cache)
 }
 
 fn parse_lines<R: BufRead>(reader: R) -> Result<Vec<Vec<State>>> {
+    parse_states(reader, false)
+}
+
+/// Parses lines that may already have beams drawn in with `|`.
+fn parse_traced_lines<R: BufRead>(reader: R) -> Result<Vec<Vec<State>>> {
+    parse_states(reader, true)
+}
+
+fn parse_states<R: BufRead>(reader: R, allow_beam: bool) -> Result<Vec<Vec<State>>> {
     let mut lines = Vec::new();
     for line in reader.lines() {
         let Ok(line) = line else {
@@ -174,7 +266,7 @@ fn parse_lines<R: BufRead>(reader: R) -> Result<Vec<Vec<State>>> {
         let line = line.trim_end_matches('\r');
         let mut states = Vec::new();
         for next in line.chars() {
-            let Some(state) = State::parse(next, false) else {
+            let Some(state) = State::parse(next, allow_beam) else {
                 return Err(AdventError::new("Encountered unknown state"));
             };
             states.push(state);
@@ -186,7 +278,10 @@ fn parse_lines<R: BufRead>(reader: R) -> Result<Vec<Vec<State>>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{count_splits, count_timelines, parse_lines};
+    use crate::source::{Semantics, Source};
+    use crate::state::State;
+    use crate::{count_splits, count_timelines, parse_lines, source_totals, trace_timelines};
+    use shared::BigInt;
     use std::io::Cursor;
 
     #[test]
@@ -203,6 +298,144 @@ mod tests {
         let lines = parse_lines(cursor).unwrap();
         let total_timelines = count_timelines(&lines);
         assert_eq!(40, total_timelines);
+        assert_eq!(BigInt::from(40u64), trace_timelines(&lines).total());
+    }
+
+    #[test]
+    fn test_deflectors() {
+        let lines = ["..S..", ".....", "..<..", ".....", ".>..."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(0, count_splits(&lines));
+        assert_timelines(1, &lines);
+    }
+
+    #[test]
+    fn test_absorber() {
+        let lines = ["..S..", "..^..", ".#..."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(1, count_splits(&lines));
+        assert_timelines(2, &lines);
+
+        // A beam absorbed before any splitter never splits
+        let lines = ["..S..", "..#..", "..^.."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(0, count_splits(&lines));
+        assert_timelines(1, &lines);
+    }
+
+    #[test]
+    fn test_gate() {
+        let lines = ["..S..", "..=..", "..^.."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(1, count_splits(&lines));
+        assert_timelines(2, &lines);
+    }
+
+    #[test]
+    fn test_wide_splitter() {
+        let lines = ["...S...", "...2...", ".^...^.", "......."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(State::WideSplitter(2), lines[1][3]);
+        assert_eq!(3, count_splits(&lines));
+        assert_timelines(4, &lines);
+
+        // Splitters on the edges still split with one beam leaving the grid
+        let lines = ["..S..", "..2..", ".....", "^...^", "....."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(3, count_splits(&lines));
+        assert_timelines(4, &lines);
+    }
+
+    #[test]
+    fn test_unknown_tile() {
+        let lines = ["..S..", "..?.."];
+        assert!(parse_lines(Cursor::new(lines.join("\n"))).is_err());
+    }
+
+    #[test]
+    fn test_iterative_deep_pyramid() {
+        // Every timeline splits on each of the 70 levels
+        let levels = 70usize;
+        let width = 2 * levels + 3;
+        let middle = width / 2;
+        let mut lines = Vec::new();
+        let mut start = vec!['.'; width];
+        start[middle] = 'S';
+        lines.push(start.iter().collect::<String>());
+        for level in 0..levels {
+            let mut line = vec!['.'; width];
+            for index in (middle - level..=middle + level).step_by(2) {
+                line[index] = '^';
+            }
+            lines.push(line.iter().collect());
+            lines.push(".".repeat(width));
+        }
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        let total = trace_timelines(&lines).total();
+        assert_eq!("1180591620717411303424", total.to_string());
+    }
+
+    #[test]
+    fn test_iterative_tall_manifold() {
+        let mut lines = vec!["S".to_string()];
+        lines.extend(std::iter::repeat_n(".".to_string(), 200_000));
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        assert_eq!(BigInt::from(1u64), trace_timelines(&lines).total());
+    }
+
+    #[test]
+    fn test_exit_histogram() {
+        let lines = parse_lines(create_example_cursor()).unwrap();
+        let histogram = trace_timelines(&lines);
+        let counts: Vec<String> = histogram.columns.iter().map(ToString::to_string).collect();
+        let expected = [
+            "1", "0", "2", "0", "10", "0", "11", "0", "11", "0", "2", "1", "1", "0", "1",
+        ];
+        assert_eq!(expected.to_vec(), counts);
+        assert!(histogram.left_edge.is_zero());
+        assert!(histogram.right_edge.is_zero());
+
+        let lines = ["..S..", "..^..", ".<.#.", ".....", "^...."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        let histogram = trace_timelines(&lines);
+        assert_eq!(BigInt::from(1u64), histogram.left_edge);
+        assert_eq!(BigInt::from(1u64), histogram.absorbed);
+        assert_eq!(BigInt::from(1u64), histogram.columns[1]);
+        assert_eq!(BigInt::from(3u64), histogram.total());
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let lines = [".S.S.", ".^.^.", ".....", "..S..", "..^.."];
+        let lines = parse_lines(Cursor::new(lines.join("\n"))).unwrap();
+        let sources = Source::find_all(&lines);
+        assert_eq!(3, sources.len());
+        assert_eq!(Source { line: 3, column: 2 }, sources[2]);
+
+        // Classically the beams into the lower source merge, so its splitter
+        // splits once
+        assert_eq!(3, count_splits(&lines));
+        let classical = source_totals(&lines, &sources, Semantics::Classical);
+        assert_eq!(BigInt::from(3u64), classical.splits);
+        assert_eq!(BigInt::from(4u64), classical.timelines);
+
+        // In quantum terms all three timelines reaching it split
+        assert_timelines(8, &lines);
+        let quantum = source_totals(&lines, &sources, Semantics::Quantum);
+        assert_eq!(BigInt::from(5u64), quantum.splits);
+        assert_eq!(BigInt::from(8u64), quantum.timelines);
+
+        let first = source_totals(&lines, &sources[..1], Semantics::Quantum);
+        assert_eq!(BigInt::from(2u64), first.splits);
+        assert_eq!(BigInt::from(3u64), first.timelines);
+        let first = source_totals(&lines, &sources[..1], Semantics::Classical);
+        assert_eq!(BigInt::from(2u64), first.splits);
+        assert_eq!(BigInt::from(3u64), first.timelines);
+    }
+
+    fn assert_timelines(expected: u64, lines: &Vec<Vec<State>>) {
+        assert_eq!(expected, count_timelines(lines));
+        assert_eq!(BigInt::from(expected), trace_timelines(lines).total());
     }
 
     fn create_example_cursor() -> Cursor<String> {
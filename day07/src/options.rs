@@ -0,0 +1,75 @@
+use crate::source::Semantics;
+use shared::{AdventError, Result};
+
+/// How part 2 counts timelines.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum TimelineCounter {
+    /// Carries a count per column down the manifold a line at a time.
+    #[default]
+    Iterative,
+    /// The original recursive search, which counts in `u64`.
+    Recursive,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub counter: TimelineCounter,
+    /// Prints where the timelines exit the manifold.
+    pub histogram: bool,
+    /// Prints the manifold with the beams drawn in.
+    pub render: bool,
+    /// Checks the beams already drawn in the input instead of solving it.
+    pub validate: bool,
+    /// Reports the splits and timelines for each source under these
+    /// semantics.
+    pub sources: Option<Semantics>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--recursive" => options.counter = TimelineCounter::Recursive,
+                "--histogram" => options.histogram = true,
+                "--render" => options.render = true,
+                "--validate" => options.validate = true,
+                "--sources" => {
+                    let semantics = args.next().and_then(|value| Semantics::parse_str(&value));
+                    let Some(semantics) = semantics else {
+                        return Err(AdventError::new(
+                            "The --sources argument needs semantics of classical or quantum",
+                        ));
+                    };
+                    options.sources = Some(semantics);
+                }
+                _ => return Err(AdventError::new("Encountered an unknown argument")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::{Options, TimelineCounter};
+    use crate::source::Semantics;
+
+    #[test]
+    fn test_parse() {
+        let args = ["--histogram", "--render"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(TimelineCounter::Iterative, options.counter);
+        assert!(options.histogram);
+        assert!(options.render);
+        assert!(!options.validate);
+        assert_eq!(None, options.sources);
+        assert!(Options::parse(["--fast".to_string()].into_iter()).is_err());
+
+        let args = ["--sources", "classical"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(Some(Semantics::Classical), options.sources);
+        let args = ["--sources"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+    }
+}
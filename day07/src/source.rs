@@ -0,0 +1,75 @@
+use crate::state::State;
+use shared::BigInt;
+use std::fmt::{Display, Formatter};
+
+/// A start tile, which sends a beam down from wherever it is in the grid.
+/// Lines and columns are numbered from 0.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Source {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// How beams from more than one source, or from more than one path, combine
+/// when they meet.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Semantics {
+    /// Beams that meet merge into one, so each splitter splits at most once
+    /// and each beam end is counted once.
+    Classical,
+    /// Every timeline is kept, so the timelines in a cell add together.
+    Quantum,
+}
+
+/// The splits and timelines caused by one or more sources. Under classical
+/// semantics the timelines are the number of beam ends.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SourceTotals {
+    pub splits: BigInt,
+    pub timelines: BigInt,
+}
+
+impl Source {
+    /// Finds every start tile, from top to bottom and left to right.
+    #[must_use]
+    pub fn find_all(lines: &[Vec<State>]) -> Vec<Self> {
+        let mut sources = Vec::new();
+        for (line, states) in lines.iter().enumerate() {
+            for (column, state) in states.iter().enumerate() {
+                if *state == State::Start {
+                    sources.push(Self { line, column });
+                }
+            }
+        }
+        sources
+    }
+
+    /// Groups the sources' columns by the line they are on.
+    #[must_use]
+    pub fn columns_by_line(sources: &[Self], line_count: usize) -> Vec<Vec<usize>> {
+        let mut columns = vec![Vec::new(); line_count];
+        for source in sources {
+            if let Some(line) = columns.get_mut(source.line) {
+                line.push(source.column);
+            }
+        }
+        columns
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line + 1, self.column + 1)
+    }
+}
+
+impl Semantics {
+    #[must_use]
+    pub fn parse_str(value: &str) -> Option<Self> {
+        match value {
+            "classical" => Some(Self::Classical),
+            "quantum" => Some(Self::Quantum),
+            _ => None,
+        }
+    }
+}
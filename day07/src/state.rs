@@ -1,9 +1,33 @@
+use std::cmp::Ordering;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum State {
     Space,
     Start,
     Beam,
     Splitter,
+    /// Sends a beam one column to the left.
+    LeftDeflector,
+    /// Sends a beam one column to the right.
+    RightDeflector,
+    /// Ends any beam that reaches it.
+    Absorber,
+    /// Lets a beam carry straight on.
+    Gate,
+    /// Splits a beam like `Splitter`, but emits the beams this many columns
+    /// to either side.
+    WideSplitter(usize),
+}
+
+/// How a beam travelling down into a tile leaves it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Propagation {
+    Straight,
+    /// Leaves one column to the left or right.
+    Deflect(Ordering),
+    /// Leaves the given number of columns to both the left and the right.
+    Split(usize),
+    Absorb,
 }
 
 impl State {
@@ -13,8 +37,61 @@ impl State {
             '.' => Some(Self::Space),
             'S' => Some(Self::Start),
             '^' => Some(Self::Splitter),
+            '<' => Some(Self::LeftDeflector),
+            '>' => Some(Self::RightDeflector),
+            '#' => Some(Self::Absorber),
+            '=' => Some(Self::Gate),
+            '2'..='9' => value
+                .to_digit(10)
+                .map(|offset| Self::WideSplitter(offset as usize)),
             '|' if allow_beam => Some(Self::Beam),
             _ => None,
         }
     }
+
+    #[must_use]
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Space => '.',
+            Self::Start => 'S',
+            Self::Beam => '|',
+            Self::Splitter => '^',
+            Self::LeftDeflector => '<',
+            Self::RightDeflector => '>',
+            Self::Absorber => '#',
+            Self::Gate => '=',
+            Self::WideSplitter(offset) => {
+                char::from_digit(u32::try_from(offset).unwrap_or(u32::MAX), 10).unwrap_or('?')
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn propagation(self) -> Propagation {
+        match self {
+            Self::Space | Self::Start | Self::Beam | Self::Gate => Propagation::Straight,
+            Self::Splitter => Propagation::Split(1),
+            Self::WideSplitter(offset) => Propagation::Split(offset),
+            Self::LeftDeflector => Propagation::Deflect(Ordering::Less),
+            Self::RightDeflector => Propagation::Deflect(Ordering::Greater),
+            Self::Absorber => Propagation::Absorb,
+        }
+    }
+}
+
+/// Moves `offset` columns in the direction of `ordering`, if that stays
+/// within a line of the given length.
+#[must_use]
+pub fn offset_index(
+    index: usize,
+    ordering: Ordering,
+    offset: usize,
+    length: usize,
+) -> Option<usize> {
+    let next_index = match ordering {
+        Ordering::Less => index.checked_sub(offset)?,
+        Ordering::Equal => index,
+        Ordering::Greater => index + offset,
+    };
+    (next_index < length).then_some(next_index)
 }
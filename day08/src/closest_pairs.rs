@@ -0,0 +1,152 @@
+use crate::connection::Connection;
+use crate::junction::Junction;
+use crate::kd_tree::KdTree;
+use crate::metric::Metric;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// How many neighbours to find for each junction at first. Each time a
+/// junction runs out, it finds twice as many.
+const INITIAL_NEIGHBOURS: usize = 4;
+
+/// Yields every pair of junctions in order of distance under the metric,
+/// compared by its exact key and with ties broken by
+/// the junctions' input order, without building all n(n-1)/2 pairs up front.
+/// Each junction walks its own nearest neighbours, and a heap holds the next
+/// pair from every junction.
+#[derive(Debug)]
+pub struct ClosestPairs<'a> {
+    junctions: &'a [Junction],
+    metric: &'a dyn Metric,
+    tree: KdTree<'a>,
+    neighbours: Vec<Neighbours>,
+    heap: BinaryHeap<Reverse<(u128, usize, usize)>>,
+}
+
+/// The nearest neighbours found so far for one junction, and how many of
+/// them have been used.
+#[derive(Debug, Default)]
+struct Neighbours {
+    found: Vec<(u128, usize)>,
+    used: usize,
+}
+
+impl<'a> ClosestPairs<'a> {
+    #[must_use]
+    pub fn new(junctions: &'a [Junction], metric: &'a dyn Metric) -> Self {
+        let mut pairs = Self {
+            junctions,
+            metric,
+            tree: KdTree::new(junctions, metric),
+            neighbours: Vec::with_capacity(junctions.len()),
+            heap: BinaryHeap::with_capacity(junctions.len()),
+        };
+        for index in 0..junctions.len() {
+            let found = pairs.tree.nearest(index, INITIAL_NEIGHBOURS);
+            pairs.neighbours.push(Neighbours { found, used: 0 });
+            pairs.queue_next(index);
+        }
+        pairs
+    }
+
+    /// Puts the junction's next nearest neighbour onto the heap, finding
+    /// more neighbours if it has used them all.
+    fn queue_next(&mut self, index: usize) {
+        let last = self.junctions.len().saturating_sub(1);
+        let neighbours = &mut self.neighbours[index];
+        if neighbours.used == neighbours.found.len() {
+            if neighbours.found.len() == last {
+                return;
+            }
+            let count = (neighbours.found.len() * 2).clamp(INITIAL_NEIGHBOURS, last);
+            neighbours.found = self.tree.nearest(index, count);
+        }
+        let (distance, other) = neighbours.found[neighbours.used];
+        neighbours.used += 1;
+        self.heap.push(Reverse((distance, index, other)));
+    }
+}
+
+impl Iterator for ClosestPairs<'_> {
+    type Item = Connection;
+
+    fn next(&mut self) -> Option<Connection> {
+        loop {
+            let Reverse((_, first_index, second_index)) = self.heap.pop()?;
+            self.queue_next(first_index);
+            // Every pair is found from both ends, so keep only the one found
+            // from the earlier junction.
+            if first_index < second_index {
+                let first = self.junctions[first_index];
+                let second = self.junctions[second_index];
+                return Some(Connection {
+                    first,
+                    second,
+                    first_index,
+                    second_index,
+                    length: self.metric.distance(&first, &second),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::closest_pairs::ClosestPairs;
+    use crate::junction::Junction;
+    use crate::metric::{Chebyshev, Euclidean, Manhattan, Metric, WeightedAxis};
+
+    #[test]
+    fn test_matches_sorted_pairs() {
+        // Small coordinates give plenty of equal distances, more so for the
+        // metrics that only add or compare the differences
+        let junctions: Vec<Junction> = (0..60u32)
+            .map(|i| Junction::from_x_y_z(i * 7 % 5, i * 5 % 4, i * 3 % 6))
+            .collect();
+        let metrics: [&dyn Metric; 4] = [
+            &Euclidean,
+            &Manhattan,
+            &Chebyshev,
+            &WeightedAxis { weights: [3, 1, 2] },
+        ];
+        for metric in metrics {
+            let mut expected = Vec::new();
+            for first in 0..junctions.len() {
+                for second in first + 1..junctions.len() {
+                    let key = metric.key(&junctions[first], &junctions[second]);
+                    expected.push((key, first, second));
+                }
+            }
+            expected.sort_unstable();
+            let actual: Vec<(u128, usize, usize)> = ClosestPairs::new(&junctions, metric)
+                .map(|c| {
+                    (
+                        metric.key(&c.first, &c.second),
+                        c.first_index,
+                        c.second_index,
+                    )
+                })
+                .collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_length() {
+        let junctions = [Junction::from_x_y_z(0, 0, 0), Junction::from_x_y_z(3, 4, 0)];
+        let connection = ClosestPairs::new(&junctions, &Manhattan).next().unwrap();
+        assert!((connection.length - 7.0).abs() < f64::EPSILON);
+        let connection = ClosestPairs::new(&junctions, &Euclidean).next().unwrap();
+        assert!((connection.length - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_too_few_junctions() {
+        assert_eq!(0, ClosestPairs::new(&[], &Euclidean).count());
+        assert_eq!(
+            0,
+            ClosestPairs::new(&[Junction::from_x_y_z(1, 2, 3)], &Euclidean).count()
+        );
+    }
+}
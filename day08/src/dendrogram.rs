@@ -0,0 +1,249 @@
+use crate::disjoint_set::DisjointSet;
+use crate::spanning_tree::SpanningTree;
+use std::fmt::Write;
+
+/// Two circuits joining. Junctions are circuits `0..n`, and the circuit
+/// made by merge `k` gets id `n + k`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeEvent {
+    pub distance: f64,
+    pub first: usize,
+    pub second: usize,
+    pub size: usize,
+}
+
+/// The full single-linkage merge history of the junctions. Joining circuits
+/// by their closest junctions is exactly what the spanning tree's edges do,
+/// in the same order.
+#[derive(Debug)]
+pub struct Dendrogram {
+    junction_count: usize,
+    merges: Vec<MergeEvent>,
+}
+
+impl Dendrogram {
+    #[must_use]
+    pub fn new(tree: &SpanningTree, junction_count: usize) -> Self {
+        let mut circuits = DisjointSet::new(junction_count);
+        // The circuit id currently held by each root junction
+        let mut circuit_ids: Vec<usize> = (0..junction_count).collect();
+        let mut merges = Vec::with_capacity(tree.edges().len());
+        for edge in tree.edges() {
+            let first = circuit_ids[circuits.find(edge.first_index)];
+            let second = circuit_ids[circuits.find(edge.second_index)];
+            circuits.union(edge.first_index, edge.second_index);
+            let root = circuits.find(edge.first_index);
+            circuit_ids[root] = junction_count + merges.len();
+            merges.push(MergeEvent {
+                distance: edge.length,
+                first: first.min(second),
+                second: first.max(second),
+                size: circuits.size(root),
+            });
+        }
+        Self {
+            junction_count,
+            merges,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn merges(&self) -> &[MergeEvent] {
+        &self.merges
+    }
+
+    /// Groups the junctions into the circuits that exist after the first
+    /// `count` merges.
+    #[must_use]
+    pub fn circuits_after_merges(&self, count: usize) -> Vec<Vec<usize>> {
+        let mut circuits = DisjointSet::new(self.junction_count);
+        let mut members: Vec<usize> = (0..self.junction_count).collect();
+        for merge in self.merges.iter().take(count) {
+            // Any junction in a circuit will do to find it again
+            let first = members[merge.first];
+            let second = members[merge.second];
+            circuits.union(first, second);
+            members.push(first);
+        }
+        circuits.sets()
+    }
+
+    /// Groups the junctions into circuits using every connection no longer
+    /// than `distance`.
+    #[must_use]
+    pub fn circuits_at_distance(&self, distance: f64) -> Vec<Vec<usize>> {
+        let count = self
+            .merges
+            .partition_point(|merge| merge.distance <= distance);
+        self.circuits_after_merges(count)
+    }
+
+    /// Finds the shortest connection length that leaves at most `circuits`
+    /// circuits, or `None` if even joining everything leaves too many.
+    #[must_use]
+    pub fn distance_for_circuits(&self, circuits: usize) -> Option<f64> {
+        if circuits >= self.junction_count {
+            return Some(0.0);
+        }
+        let merges_needed = self.junction_count - circuits;
+        self.merges
+            .get(merges_needed.checked_sub(1)?)
+            .map(|merge| merge.distance)
+    }
+
+    /// Writes the tree in Newick format, with junctions labelled `j0`, `j1`
+    /// and so on, and branch lengths being the difference in merge distance.
+    /// This walks the tree with its own stack, since a chain of junctions
+    /// can make it as deep as there are junctions.
+    #[must_use]
+    pub fn to_newick(&self) -> String {
+        let roots: Vec<usize> = self.roots();
+        let mut newick = String::new();
+        if roots.len() > 1 {
+            newick.push('(');
+        }
+        for (index, root) in roots.iter().enumerate() {
+            if index > 0 {
+                newick.push(',');
+            }
+            self.write_newick(*root, &mut newick);
+        }
+        if roots.len() > 1 {
+            newick.push(')');
+        }
+        newick.push_str(";\n");
+        newick
+    }
+
+    /// Finds the circuits that never merge into anything else.
+    fn roots(&self) -> Vec<usize> {
+        let total = self.junction_count + self.merges.len();
+        let mut merged = vec![false; total];
+        for merge in &self.merges {
+            merged[merge.first] = true;
+            merged[merge.second] = true;
+        }
+        (0..total).filter(|id| !merged[*id]).collect()
+    }
+
+    fn height(&self, id: usize) -> f64 {
+        id.checked_sub(self.junction_count)
+            .map_or(0.0, |merge| self.merges[merge].distance)
+    }
+
+    fn write_newick(&self, root: usize, newick: &mut String) {
+        enum Step {
+            Open(usize, Option<f64>),
+            Separator,
+            Close(usize, Option<f64>),
+        }
+        let mut stack = vec![Step::Open(root, None)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Open(id, parent_height) => {
+                    if let Some(merge) = id
+                        .checked_sub(self.junction_count)
+                        .map(|index| &self.merges[index])
+                    {
+                        let height = Some(merge.distance);
+                        newick.push('(');
+                        stack.push(Step::Close(id, parent_height));
+                        stack.push(Step::Open(merge.second, height));
+                        stack.push(Step::Separator);
+                        stack.push(Step::Open(merge.first, height));
+                    } else {
+                        let _ = write!(newick, "j{id}");
+                        self.write_branch_length(id, parent_height, newick);
+                    }
+                }
+                Step::Separator => newick.push(','),
+                Step::Close(id, parent_height) => {
+                    newick.push(')');
+                    self.write_branch_length(id, parent_height, newick);
+                }
+            }
+        }
+    }
+
+    fn write_branch_length(&self, id: usize, parent_height: Option<f64>, newick: &mut String) {
+        if let Some(parent_height) = parent_height {
+            let _ = write!(newick, ":{:.3}", parent_height - self.height(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::closest_pairs::ClosestPairs;
+    use crate::dendrogram::{Dendrogram, MergeEvent};
+    use crate::junction::Junction;
+    use crate::metric::Euclidean;
+    use crate::spanning_tree::SpanningTree;
+
+    fn create_dendrogram() -> Dendrogram {
+        let junctions = [
+            Junction::from_x_y_z(0, 0, 0),
+            Junction::from_x_y_z(3, 4, 0),
+            Junction::from_x_y_z(0, 1, 0),
+            Junction::from_x_y_z(10, 4, 0),
+        ];
+        let tree = SpanningTree::build(ClosestPairs::new(&junctions, &Euclidean), junctions.len());
+        Dendrogram::new(&tree, junctions.len())
+    }
+
+    #[test]
+    fn test_merges() {
+        let dendrogram = create_dendrogram();
+        let expected = vec![
+            MergeEvent {
+                distance: 1.0,
+                first: 0,
+                second: 2,
+                size: 2,
+            },
+            MergeEvent {
+                distance: 18f64.sqrt(),
+                first: 1,
+                second: 4,
+                size: 3,
+            },
+            MergeEvent {
+                distance: 7.0,
+                first: 3,
+                second: 5,
+                size: 4,
+            },
+        ];
+        assert_eq!(expected, dendrogram.merges());
+    }
+
+    #[test]
+    fn test_queries() {
+        let dendrogram = create_dendrogram();
+        assert_eq!(4, dendrogram.circuits_after_merges(0).len());
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![3]],
+            dendrogram.circuits_after_merges(2)
+        );
+        assert_eq!(
+            vec![vec![0, 2], vec![1], vec![3]],
+            dendrogram.circuits_at_distance(4.0)
+        );
+        assert_eq!(1, dendrogram.circuits_at_distance(7.0).len());
+
+        assert_eq!(Some(0.0), dendrogram.distance_for_circuits(4));
+        assert_eq!(Some(1.0), dendrogram.distance_for_circuits(3));
+        assert_eq!(Some(7.0), dendrogram.distance_for_circuits(1));
+        assert_eq!(None, dendrogram.distance_for_circuits(0));
+    }
+
+    #[test]
+    fn test_newick() {
+        let dendrogram = create_dendrogram();
+        assert_eq!(
+            "(j3:7.000,(j1:4.243,(j0:1.000,j2:1.000):3.243):2.757);\n",
+            dendrogram.to_newick()
+        );
+    }
+}
@@ -0,0 +1,93 @@
+/// Groups items, identified by index, into disjoint sets that can be merged.
+/// Finding a set compresses the path to its root, and merging hangs the
+/// smaller set under the larger, so both are close to constant time.
+#[derive(Debug)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    /// The size of each set, only kept up to date at its root.
+    sizes: Vec<usize>,
+}
+
+impl DisjointSet {
+    /// Starts with every item in a set of its own.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    /// Finds the root of the set containing the item.
+    pub fn find(&mut self, item: usize) -> usize {
+        let mut root = item;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = item;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing both items. Returns `false` if they were
+    /// already in the same set.
+    pub fn union(&mut self, first: usize, second: usize) -> bool {
+        let first = self.find(first);
+        let second = self.find(second);
+        if first == second {
+            return false;
+        }
+        let (larger, smaller) = if self.sizes[first] >= self.sizes[second] {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        self.parents[smaller] = larger;
+        self.sizes[larger] += self.sizes[smaller];
+        true
+    }
+
+    /// Gets the size of the set containing the item.
+    pub fn size(&mut self, item: usize) -> usize {
+        let root = self.find(item);
+        self.sizes[root]
+    }
+
+    /// Lists the items in each set, in order of each set's smallest item.
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut set_indexes = vec![usize::MAX; self.parents.len()];
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+        for item in 0..self.parents.len() {
+            let root = self.find(item);
+            if set_indexes[root] == usize::MAX {
+                set_indexes[root] = sets.len();
+                sets.push(Vec::with_capacity(self.sizes[root]));
+            }
+            sets[set_indexes[root]].push(item);
+        }
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disjoint_set::DisjointSet;
+
+    #[test]
+    fn test_union_and_find() {
+        let mut set = DisjointSet::new(5);
+        assert!(set.union(0, 1));
+        assert!(set.union(3, 4));
+        assert!(set.union(1, 4));
+        assert!(!set.union(0, 3));
+        assert_eq!(4, set.size(3));
+        assert_eq!(1, set.size(2));
+        assert_eq!(set.find(0), set.find(4));
+        assert_ne!(set.find(0), set.find(2));
+        assert_eq!(vec![vec![0, 1, 3, 4], vec![2]], set.sets());
+    }
+}
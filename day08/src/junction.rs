@@ -11,4 +11,25 @@ impl Junction {
     pub fn from_x_y_z(x: u32, y: u32, z: u32) -> Self {
         Junction { x, y, z }
     }
+
+    #[inline]
+    #[must_use]
+    pub fn coordinates(&self) -> [u32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// Gets the exact squared straight-line distance, which orders the same
+    /// way as the distance itself. Three squared `u32` differences can
+    /// overflow a `u64`, so this needs a `u128`.
+    #[must_use]
+    pub fn squared_distance(&self, other: &Junction) -> u128 {
+        self.coordinates()
+            .iter()
+            .zip(other.coordinates())
+            .map(|(a, b)| {
+                let difference = u128::from(a.abs_diff(b));
+                difference * difference
+            })
+            .sum()
+    }
 }
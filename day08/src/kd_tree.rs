@@ -0,0 +1,137 @@
+use crate::junction::Junction;
+use crate::metric::Metric;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// A balanced k-d tree over junction indexes, split on X, Y and Z in turn.
+/// The tree is implicit: each range of `indexes` has its splitting junction
+/// in the middle, with the junctions before it on the split axis to its left
+/// and the rest to its right. Distances are measured with `metric`.
+#[derive(Debug)]
+pub struct KdTree<'a> {
+    junctions: &'a [Junction],
+    metric: &'a dyn Metric,
+    indexes: Vec<usize>,
+}
+
+impl<'a> KdTree<'a> {
+    #[must_use]
+    pub fn new(junctions: &'a [Junction], metric: &'a dyn Metric) -> Self {
+        let mut indexes: Vec<usize> = (0..junctions.len()).collect();
+        Self::build(junctions, &mut indexes, 0);
+        Self {
+            junctions,
+            metric,
+            indexes,
+        }
+    }
+
+    fn build(junctions: &[Junction], indexes: &mut [usize], depth: usize) {
+        if indexes.len() <= 1 {
+            return;
+        }
+        let axis = depth % 3;
+        let middle = indexes.len() / 2;
+        indexes.select_nth_unstable_by_key(middle, |index| {
+            (junctions[*index].coordinates()[axis], *index)
+        });
+        let (left, right) = indexes.split_at_mut(middle);
+        Self::build(junctions, left, depth + 1);
+        Self::build(junctions, &mut right[1..], depth + 1);
+    }
+
+    /// Finds the `count` junctions nearest to the junction at `index`, not
+    /// counting itself, as metric keys and indexes. Junctions at the
+    /// same distance are ordered by index, so asking for more neighbours
+    /// always extends the same list.
+    #[must_use]
+    pub fn nearest(&self, index: usize, count: usize) -> Vec<(u128, usize)> {
+        let mut nearest = BinaryHeap::with_capacity(count + 1);
+        if count > 0 {
+            self.search(index, count, 0..self.indexes.len(), 0, &mut nearest);
+        }
+        nearest.into_sorted_vec()
+    }
+
+    fn search(
+        &self,
+        target: usize,
+        count: usize,
+        range: Range<usize>,
+        depth: usize,
+        nearest: &mut BinaryHeap<(u128, usize)>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let middle = range.start + range.len() / 2;
+        let index = self.indexes[middle];
+        let junction = &self.junctions[index];
+        let target_junction = &self.junctions[target];
+        if index != target {
+            let candidate = (self.metric.key(junction, target_junction), index);
+            if nearest.len() < count {
+                nearest.push(candidate);
+            } else if nearest.peek().is_some_and(|worst| candidate < *worst) {
+                nearest.pop();
+                nearest.push(candidate);
+            }
+        }
+
+        // Search the side of the split holding the target first, and only
+        // cross the split if something there could still be near enough.
+        let axis = depth % 3;
+        let target_value = target_junction.coordinates()[axis];
+        let split_value = junction.coordinates()[axis];
+        let (near, far) = if (target_value, target) < (split_value, index) {
+            (range.start..middle, middle + 1..range.end)
+        } else {
+            (middle + 1..range.end, range.start..middle)
+        };
+        self.search(target, count, near, depth + 1, nearest);
+        let plane_distance = self
+            .metric
+            .axis_key(axis, target_value.abs_diff(split_value));
+        let crosses = nearest.len() < count
+            || nearest
+                .peek()
+                .is_some_and(|(worst, _)| plane_distance <= *worst);
+        if crosses {
+            self.search(target, count, far, depth + 1, nearest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::junction::Junction;
+    use crate::kd_tree::KdTree;
+    use crate::metric::{Chebyshev, Euclidean, Manhattan, Metric, WeightedAxis};
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let junctions: Vec<Junction> = (0..200u32)
+            .map(|i| Junction::from_x_y_z(i * 7 % 13, i * 5 % 11, i * 3 % 17))
+            .collect();
+        let metrics: [&dyn Metric; 4] = [
+            &Euclidean,
+            &Manhattan,
+            &Chebyshev,
+            &WeightedAxis { weights: [1, 5, 2] },
+        ];
+        for metric in metrics {
+            let tree = KdTree::new(&junctions, metric);
+            for index in [0, 57, 199] {
+                let mut expected: Vec<(u128, usize)> = (0..junctions.len())
+                    .filter(|other| *other != index)
+                    .map(|other| (metric.key(&junctions[index], &junctions[other]), other))
+                    .collect();
+                expected.sort_unstable();
+                for count in [1, 5, 40, 199, 500] {
+                    let nearest = tree.nearest(index, count);
+                    assert_eq!(&expected[..count.min(expected.len())], nearest.as_slice());
+                }
+            }
+        }
+    }
+}
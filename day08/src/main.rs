@@ -1,58 +1,129 @@
 use crate::circuit::Circuit;
+use crate::closest_pairs::ClosestPairs;
 use crate::connection::Connection;
+use crate::dendrogram::Dendrogram;
+use crate::disjoint_set::DisjointSet;
 use crate::junction::Junction;
+use crate::options::{DendrogramQuery, ExportFormat, Options};
+use crate::spanning_tree::SpanningTree;
 use shared::{AdventError, Result};
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::collections::hash_map::Entry::Vacant;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::ops::Deref;
 use std::path::Path;
-use std::rc::Rc;
 
 mod circuit;
+mod closest_pairs;
 mod connection;
+mod dendrogram;
+mod disjoint_set;
 mod junction;
+mod kd_tree;
+mod metric;
+mod options;
+mod spanning_tree;
 
 fn main() -> Result<()> {
     let path = Path::new("day08/resources/input.txt");
-    run_part1(path)?;
-    run_part2(path)?;
+    let options = Options::parse(std::env::args().skip(1))?;
+    run_part1(path, &options)?;
+    run_part2(path, &options)?;
     Ok(())
 }
 
-fn run_part1(path: &Path) -> Result<()> {
+fn run_part1(path: &Path, options: &Options) -> Result<()> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
     let reader = BufReader::new(file);
     let junctions = parse_junctions(reader)?;
-    let mut connections = create_combinations(&junctions);
-    sort_combinations(&mut connections);
-    let mut circuits = connect_combinations(&connections, 1_000, false, junctions.len());
+    let connections = ClosestPairs::new(&junctions, options.metric.as_ref());
+    let mut circuits = connect_combinations(connections, 1_000, junctions.len());
+    let mut circuits = create_circuits(&mut circuits, &junctions);
     sort_circuits_by_total_connections(&mut circuits);
-    let product = circuits
-        .iter()
-        .take(3)
-        .map(|c| c.borrow().len())
-        .product::<usize>();
+    let product = circuits.iter().take(3).map(Circuit::len).product::<usize>();
     println!("Part 1 - Product: {product}");
     Ok(())
 }
 
-fn run_part2(path: &Path) -> Result<()> {
+fn run_part2(path: &Path, options: &Options) -> Result<()> {
     let Ok(file) = File::open(path) else {
         return Err(AdventError::new("Could not open the input file"));
     };
     let reader = BufReader::new(file);
     let junctions = parse_junctions(reader)?;
-    let mut connections = create_combinations(&junctions);
-    sort_combinations(&mut connections);
-    connect_combinations(&connections, connections.len(), true, junctions.len());
+    let connections = ClosestPairs::new(&junctions, options.metric.as_ref());
+    let tree = SpanningTree::build(connections, junctions.len());
+    let Some(connection) = tree.final_edge() else {
+        return Err(AdventError::new(
+            "The junctions could not all be connected into one circuit",
+        ));
+    };
+    let distance = u64::from(connection.first.x) * u64::from(connection.second.x);
+    println!("Part 2 - Distance: {distance}");
+    println!("Part 2 - Final connection length: {:.3}", connection.length);
+    println!(
+        "Part 2 - {} connections, total cable length: {:.3}",
+        tree.edges().len(),
+        tree.total_length()
+    );
+    if let Some(export) = &options.export {
+        let contents = match export.format {
+            ExportFormat::Dot => tree.to_dot(&junctions),
+            ExportFormat::Csv => tree.to_csv(),
+        };
+        write_output(&export.path, &contents)?;
+    }
+    if options.merges || options.newick.is_some() || !options.queries.is_empty() {
+        let dendrogram = Dendrogram::new(&tree, junctions.len());
+        print_dendrogram(&dendrogram, options);
+        if let Some(path) = &options.newick {
+            write_output(path, &dendrogram.to_newick())?;
+        }
+    }
     Ok(())
 }
 
+fn write_output(path: &Path, contents: &str) -> Result<()> {
+    if std::fs::write(path, contents).is_err() {
+        return Err(AdventError::new(format!(
+            "Could not write to {}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+fn print_dendrogram(dendrogram: &Dendrogram, options: &Options) {
+    if options.merges {
+        for merge in dendrogram.merges() {
+            println!(
+                "Merge at {:.3}: circuits {} and {} make {} junctions",
+                merge.distance, merge.first, merge.second, merge.size
+            );
+        }
+    }
+    for query in &options.queries {
+        match query {
+            DendrogramQuery::CircuitsAtDistance(distance) => {
+                let circuits = dendrogram.circuits_at_distance(*distance);
+                println!("Circuits at distance {distance}: {}", circuits.len());
+            }
+            DendrogramQuery::CircuitsAfterMerges(merges) => {
+                let circuits = dendrogram.circuits_after_merges(*merges);
+                println!("Circuits after {merges} merges: {}", circuits.len());
+            }
+            DendrogramQuery::DistanceForCircuits(circuits) => {
+                match dendrogram.distance_for_circuits(*circuits) {
+                    Some(distance) => {
+                        println!("Distance for at most {circuits} circuits: {distance:.3}");
+                    }
+                    None => println!("No distance leaves at most {circuits} circuits"),
+                }
+            }
+        }
+    }
+}
+
 fn parse_junctions<R: BufRead>(reader: R) -> Result<Vec<Junction>> {
     let mut junctions = Vec::new();
     for line in reader.lines() {
@@ -87,101 +158,40 @@ fn parse_junctions<R: BufRead>(reader: R) -> Result<Vec<Junction>> {
     Ok(junctions)
 }
 
-fn create_combinations(junctions: &[Junction]) -> Vec<Connection> {
-    let mut connections = Vec::new();
-    for outer_index in 0..junctions.len() {
-        let first = junctions[outer_index];
-        for inner_index in (outer_index + 1)..junctions.len() {
-            let second = junctions[inner_index];
-            let connection = Connection { first, second };
-            connections.push(connection);
-        }
-    }
-    connections
-}
-
-fn sort_combinations(connection: &mut [Connection]) {
-    connection.sort_by(|a, b| {
-        let a_distance = a.distance();
-        let b_distance = b.distance();
-        a_distance.partial_cmp(&b_distance).unwrap()
-    });
-}
-
+/// Makes up to `max_connections` of the connections in order, and returns
+/// the junctions grouped into circuits.
 fn connect_combinations(
-    combinations: &[Connection],
+    combinations: impl Iterator<Item = Connection>,
     max_connections: usize,
-    part2: bool,
     total_junctions: usize,
-) -> Vec<Rc<RefCell<Circuit>>> {
-    let mut unique_circuits: HashMap<*const Circuit, Rc<RefCell<Circuit>>> = HashMap::new();
-    let mut junction_circuits: HashMap<Junction, Rc<RefCell<Circuit>>> = HashMap::new();
-    for combination in combinations.iter().take(max_connections) {
-        let first_circuit = junction_circuits.get(&combination.first);
-        let second_circuit = junction_circuits.get(&combination.second);
-        match (first_circuit, second_circuit) {
-            (None, None) => {
-                let mut new_circuit = Circuit::new();
-                new_circuit.add(combination.first);
-                new_circuit.add(combination.second);
-                let new_circuit = Rc::new(RefCell::new(new_circuit));
-                junction_circuits.insert(combination.first, Rc::clone(&new_circuit));
-                junction_circuits.insert(combination.second, Rc::clone(&new_circuit));
-                if let Vacant(entry) = unique_circuits.entry(new_circuit.as_ptr()) {
-                    entry.insert(new_circuit);
-                }
-            }
-            (Some(first_circuit), None) => {
-                first_circuit.borrow_mut().add(combination.second);
-                junction_circuits.insert(combination.second, Rc::clone(first_circuit));
-            }
-            (None, Some(second_circuit)) => {
-                second_circuit.borrow_mut().add(combination.first);
-                junction_circuits.insert(combination.first, Rc::clone(second_circuit));
-            }
-            (Some(first_circuit), Some(second_circuit)) => {
-                if first_circuit.as_ptr() != second_circuit.as_ptr() {
-                    unique_circuits.remove(&std::ptr::from_ref::<Circuit>(
-                        first_circuit.borrow().deref(),
-                    ));
-                    unique_circuits.remove(&std::ptr::from_ref::<Circuit>(
-                        second_circuit.borrow().deref(),
-                    ));
-
-                    let new_circuit = first_circuit
-                        .borrow()
-                        .merge(second_circuit.borrow().deref());
-                    let new_circuit = Rc::new(RefCell::new(new_circuit));
-                    for junction in new_circuit.borrow().junctions() {
-                        junction_circuits.insert(*junction, Rc::clone(&new_circuit));
-                    }
-                    unique_circuits.insert(new_circuit.as_ptr(), new_circuit);
-                }
-            }
-        }
-        if part2
-            && unique_circuits.len() == 1
-            && let Some(circuit) = unique_circuits.values().next()
-            && circuit.borrow().len() == total_junctions
-        {
-            let distance = combination.first.x * combination.second.x;
-            println!("Part 2 - Distance: {distance}");
-            break;
-        }
+) -> DisjointSet {
+    let mut circuits = DisjointSet::new(total_junctions);
+    for combination in combinations.take(max_connections) {
+        circuits.union(combination.first_index, combination.second_index);
     }
-    unique_circuits.into_values().collect()
+    circuits
+}
+
+fn create_circuits(circuits: &mut DisjointSet, junctions: &[Junction]) -> Vec<Circuit> {
+    circuits
+        .sets()
+        .iter()
+        .map(|indexes| Circuit::from_indexes(indexes, junctions))
+        .collect()
 }
 
-fn sort_circuits_by_total_connections(circuits: &mut [Rc<RefCell<Circuit>>]) {
-    circuits.sort_by(|a, b| a.borrow().len().cmp(&b.borrow().len()).reverse());
+fn sort_circuits_by_total_connections(circuits: &mut [Circuit]) {
+    circuits.sort_by(|a, b| a.len().cmp(&b.len()).reverse());
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::closest_pairs::ClosestPairs;
     use crate::junction::Junction;
+    use crate::metric::{Chebyshev, Euclidean};
+    use crate::spanning_tree::SpanningTree;
     use crate::{
-        connect_combinations, create_combinations, parse_junctions,
-        sort_circuits_by_total_connections, sort_combinations,
+        connect_combinations, create_circuits, parse_junctions, sort_circuits_by_total_connections,
     };
     use std::io::Cursor;
 
@@ -190,20 +200,48 @@ mod tests {
         let cursor = create_cursor();
         let junctions = parse_junctions(cursor).unwrap();
         assert_eq!(20, junctions.len());
-        let mut combinations = create_combinations(&junctions);
-        sort_combinations(&mut combinations);
-        let closest = combinations.first().unwrap();
+        let closest = ClosestPairs::new(&junctions, &Euclidean).next().unwrap();
         assert_eq!(Junction::from_x_y_z(162, 817, 812), closest.first);
         assert_eq!(Junction::from_x_y_z(425, 690, 689), closest.second);
 
-        let mut circuits = connect_combinations(&combinations, 10, false, junctions.len());
+        let combinations = ClosestPairs::new(&junctions, &Euclidean);
+        let mut circuits = connect_combinations(combinations, 10, junctions.len());
+        assert_eq!(11, circuits.sets().len());
+        let mut circuits = create_circuits(&mut circuits, &junctions);
         sort_circuits_by_total_connections(&mut circuits);
         let [a, b, c, ..] = circuits.as_slice() else {
             panic!("There should have been at least 3 circuits");
         };
-        assert_eq!(5, a.borrow().len());
-        assert_eq!(4, b.borrow().len());
-        assert_eq!(2, c.borrow().len());
+        assert_eq!(5, a.len());
+        assert_eq!(4, b.len());
+        assert_eq!(2, c.len());
+    }
+
+    #[test]
+    fn test_part2_example() {
+        let junctions = parse_junctions(create_cursor()).unwrap();
+        let tree = SpanningTree::build(ClosestPairs::new(&junctions, &Euclidean), junctions.len());
+        assert_eq!(19, tree.edges().len());
+        let connection = tree.final_edge().unwrap();
+        assert_eq!(216, connection.first.x);
+        assert_eq!(117, connection.second.x);
+    }
+
+    #[test]
+    fn test_part2_example_chebyshev() {
+        let junctions = parse_junctions(create_cursor()).unwrap();
+        let tree = SpanningTree::build(ClosestPairs::new(&junctions, &Chebyshev), junctions.len());
+        assert_eq!(19, tree.edges().len());
+        // Every connection is as long as its largest axis difference
+        for edge in tree.edges() {
+            let first = edge.first.coordinates();
+            let second = edge.second.coordinates();
+            let largest = (0..3)
+                .map(|axis| first[axis].abs_diff(second[axis]))
+                .max()
+                .unwrap();
+            assert!((edge.length - f64::from(largest)).abs() < f64::EPSILON);
+        }
     }
 
     fn create_cursor() -> Cursor<String> {
@@ -0,0 +1,200 @@
+use crate::junction::Junction;
+use std::fmt::Debug;
+
+/// A way of measuring how far apart two junctions are. Connections are
+/// ordered by an exact integer key rather than the floating point distance,
+/// so equal distances really do compare equal.
+pub trait Metric: Debug {
+    /// Gets a key that orders pairs of junctions the same way as their
+    /// distance.
+    fn key(&self, first: &Junction, second: &Junction) -> u128;
+
+    /// Gets the smallest key two junctions can have when their coordinates
+    /// on the axis differ by `difference`, which lets a search skip any
+    /// junctions too far away on that axis alone.
+    fn axis_key(&self, axis: usize, difference: u32) -> u128;
+
+    fn distance(&self, first: &Junction, second: &Junction) -> f64;
+}
+
+/// Straight-line distance, keyed by the squared distance.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Euclidean;
+
+/// The sum of the distances along each axis.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Manhattan;
+
+/// The largest distance along any one axis.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Chebyshev;
+
+/// Straight-line distance where a step along each of the X, Y and Z axes is
+/// scaled by the square root of its weight, keyed by the weighted sum of
+/// squared differences.
+#[derive(Debug, Copy, Clone)]
+pub struct WeightedAxis {
+    pub weights: [u32; 3],
+}
+
+impl Metric for Euclidean {
+    #[inline]
+    fn key(&self, first: &Junction, second: &Junction) -> u128 {
+        first.squared_distance(second)
+    }
+
+    #[inline]
+    fn axis_key(&self, _axis: usize, difference: u32) -> u128 {
+        u128::from(difference).pow(2)
+    }
+
+    fn distance(&self, first: &Junction, second: &Junction) -> f64 {
+        differences(first, second)
+            .iter()
+            .map(|difference| f64::from(*difference).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl Metric for Manhattan {
+    #[inline]
+    fn key(&self, first: &Junction, second: &Junction) -> u128 {
+        differences(first, second)
+            .iter()
+            .copied()
+            .map(u128::from)
+            .sum()
+    }
+
+    #[inline]
+    fn axis_key(&self, _axis: usize, difference: u32) -> u128 {
+        u128::from(difference)
+    }
+
+    fn distance(&self, first: &Junction, second: &Junction) -> f64 {
+        differences(first, second)
+            .iter()
+            .copied()
+            .map(f64::from)
+            .sum()
+    }
+}
+
+impl Metric for Chebyshev {
+    #[inline]
+    fn key(&self, first: &Junction, second: &Junction) -> u128 {
+        differences(first, second)
+            .iter()
+            .copied()
+            .map(u128::from)
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[inline]
+    fn axis_key(&self, _axis: usize, difference: u32) -> u128 {
+        u128::from(difference)
+    }
+
+    fn distance(&self, first: &Junction, second: &Junction) -> f64 {
+        differences(first, second)
+            .iter()
+            .copied()
+            .map(f64::from)
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Metric for WeightedAxis {
+    #[inline]
+    fn key(&self, first: &Junction, second: &Junction) -> u128 {
+        let differences = differences(first, second);
+        (0..3)
+            .map(|axis| self.axis_key(axis, differences[axis]))
+            .sum()
+    }
+
+    #[inline]
+    fn axis_key(&self, axis: usize, difference: u32) -> u128 {
+        u128::from(self.weights[axis]) * u128::from(difference).pow(2)
+    }
+
+    fn distance(&self, first: &Junction, second: &Junction) -> f64 {
+        differences(first, second)
+            .iter()
+            .zip(self.weights)
+            .map(|(difference, weight)| f64::from(weight) * f64::from(*difference).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+fn differences(first: &Junction, second: &Junction) -> [u32; 3] {
+    let first = first.coordinates();
+    let second = second.coordinates();
+    [0, 1, 2].map(|axis| first[axis].abs_diff(second[axis]))
+}
+
+/// Parses `euclidean`, `manhattan`, `chebyshev` or `weighted:X,Y,Z` with a
+/// whole number weight for each axis.
+#[must_use]
+pub fn parse_metric(value: &str) -> Option<Box<dyn Metric>> {
+    match value {
+        "euclidean" => Some(Box::new(Euclidean)),
+        "manhattan" => Some(Box::new(Manhattan)),
+        "chebyshev" => Some(Box::new(Chebyshev)),
+        _ => {
+            let weights = value.strip_prefix("weighted:")?;
+            let weights: Vec<u32> = weights
+                .split(',')
+                .map(|weight| weight.parse().ok())
+                .collect::<Option<_>>()?;
+            let weights = weights.try_into().ok()?;
+            Some(Box::new(WeightedAxis { weights }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::junction::Junction;
+    use crate::metric::{Chebyshev, Euclidean, Manhattan, Metric, WeightedAxis, parse_metric};
+
+    #[test]
+    fn test_euclidean() {
+        let first = Junction::from_x_y_z(162, 817, 812);
+        let second = Junction::from_x_y_z(425, 690, 689);
+        let distance = Euclidean.distance(&first, &second);
+        assert!(distance < 316.903);
+        assert!(distance > 316.902);
+        assert_eq!(100_427, Euclidean.key(&first, &second));
+    }
+
+    #[test]
+    fn test_other_metrics() {
+        let first = Junction::from_x_y_z(1, 10, 4);
+        let second = Junction::from_x_y_z(4, 6, 4);
+        assert_eq!(7, Manhattan.key(&first, &second));
+        assert!((Manhattan.distance(&first, &second) - 7.0).abs() < f64::EPSILON);
+        assert_eq!(4, Chebyshev.key(&first, &second));
+        assert!((Chebyshev.distance(&first, &second) - 4.0).abs() < f64::EPSILON);
+
+        let weighted = WeightedAxis { weights: [4, 1, 9] };
+        assert_eq!(52, weighted.key(&first, &second));
+        assert!((weighted.distance(&first, &second) - 52f64.sqrt()).abs() < 1e-12);
+        assert_eq!(36, weighted.axis_key(0, 3));
+    }
+
+    #[test]
+    fn test_parse_metric() {
+        assert!(parse_metric("manhattan").is_some());
+        let metric = parse_metric("weighted:1,2,3").unwrap();
+        let first = Junction::from_x_y_z(0, 0, 0);
+        let second = Junction::from_x_y_z(1, 1, 1);
+        assert_eq!(6, metric.key(&first, &second));
+        assert!(parse_metric("weighted:1,2").is_none());
+        assert!(parse_metric("weighted:1,x,3").is_none());
+        assert!(parse_metric("taxicab").is_none());
+    }
+}
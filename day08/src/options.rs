@@ -0,0 +1,204 @@
+use crate::metric::{Euclidean, Metric, parse_metric};
+use shared::{AdventError, Result};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The formats the minimum spanning tree can be exported in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExportFormat {
+    Dot,
+    Csv,
+}
+
+/// Where to write the minimum spanning tree, and in which format.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Export {
+    pub format: ExportFormat,
+    pub path: PathBuf,
+}
+
+/// A question about how the junctions cluster as connections are made.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DendrogramQuery {
+    /// The circuits using every connection up to this length.
+    CircuitsAtDistance(f64),
+    /// The circuits after this many merges.
+    CircuitsAfterMerges(usize),
+    /// The connection length needed for at most this many circuits.
+    DistanceForCircuits(usize),
+}
+
+#[derive(Debug)]
+pub struct Options {
+    /// How the distance between two junctions is measured.
+    pub metric: Box<dyn Metric>,
+    /// Writes the minimum spanning tree to a file after part 2.
+    pub export: Option<Export>,
+    /// Prints every merge of two circuits.
+    pub merges: bool,
+    /// Writes the merge history as a Newick tree to this file.
+    pub newick: Option<PathBuf>,
+    pub queries: Vec<DendrogramQuery>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            metric: Box::new(Euclidean),
+            export: None,
+            merges: false,
+            newick: None,
+            queries: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--export" => {
+                    let format = match args.next().as_deref() {
+                        Some("dot") => ExportFormat::Dot,
+                        Some("csv") => ExportFormat::Csv,
+                        _ => {
+                            return Err(AdventError::new(
+                                "The --export argument needs a format of dot or csv",
+                            ));
+                        }
+                    };
+                    let Some(path) = args.next() else {
+                        return Err(AdventError::new(
+                            "The --export argument needs a file to write to",
+                        ));
+                    };
+                    options.export = Some(Export {
+                        format,
+                        path: PathBuf::from(path),
+                    });
+                }
+                "--metric" => {
+                    let Some(metric) = args.next().as_deref().and_then(parse_metric) else {
+                        return Err(AdventError::new(
+                            "The --metric argument needs euclidean, manhattan, chebyshev or weighted:X,Y,Z",
+                        ));
+                    };
+                    options.metric = metric;
+                }
+                "--merges" => options.merges = true,
+                "--newick" => {
+                    let Some(path) = args.next() else {
+                        return Err(AdventError::new(
+                            "The --newick argument needs a file to write to",
+                        ));
+                    };
+                    options.newick = Some(PathBuf::from(path));
+                }
+                "--circuits-at" => {
+                    let distance: f64 = parse_value(args.next(), "--circuits-at")?;
+                    if !distance.is_finite() || distance < 0.0 {
+                        return Err(AdventError::new(
+                            "The --circuits-at argument needs a distance of zero or more",
+                        ));
+                    }
+                    options
+                        .queries
+                        .push(DendrogramQuery::CircuitsAtDistance(distance));
+                }
+                "--circuits-after" => {
+                    let merges = parse_value(args.next(), "--circuits-after")?;
+                    options
+                        .queries
+                        .push(DendrogramQuery::CircuitsAfterMerges(merges));
+                }
+                "--distance-for" => {
+                    let circuits = parse_value(args.next(), "--distance-for")?;
+                    options
+                        .queries
+                        .push(DendrogramQuery::DistanceForCircuits(circuits));
+                }
+                _ => return Err(AdventError::new("Encountered an unknown argument")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_value<T: FromStr>(value: Option<String>, name: &str) -> Result<T> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| AdventError::new(format!("The {name} argument needs a valid number")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::junction::Junction;
+    use crate::options::{DendrogramQuery, Export, ExportFormat, Options};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse() {
+        let options = Options::parse(std::iter::empty()).unwrap();
+        assert_eq!(None, options.export);
+        let args = ["--export", "csv", "tree.csv"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        let expected = Export {
+            format: ExportFormat::Csv,
+            path: PathBuf::from("tree.csv"),
+        };
+        assert_eq!(Some(expected), options.export);
+        let args = ["--export", "svg", "tree.svg"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        let args = ["--export", "dot"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_parse_metric() {
+        let first = Junction::from_x_y_z(0, 0, 0);
+        let second = Junction::from_x_y_z(1, 2, 2);
+        let options = Options::parse(std::iter::empty()).unwrap();
+        assert_eq!(9, options.metric.key(&first, &second));
+        let args = ["--metric", "manhattan"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(5, options.metric.key(&first, &second));
+        let args = ["--metric", "weighted:1,1,2"];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(13, options.metric.key(&first, &second));
+        let args = ["--metric", "hamming"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        assert!(Options::parse(["--metric".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_parse_dendrogram() {
+        let args = [
+            "--newick",
+            "tree.nwk",
+            "--circuits-at",
+            "12.5",
+            "--circuits-after",
+            "1000",
+            "--distance-for",
+            "3",
+        ];
+        let options = Options::parse(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(Some(PathBuf::from("tree.nwk")), options.newick);
+        assert!(!options.merges);
+        let expected = vec![
+            DendrogramQuery::CircuitsAtDistance(12.5),
+            DendrogramQuery::CircuitsAfterMerges(1_000),
+            DendrogramQuery::DistanceForCircuits(3),
+        ];
+        assert_eq!(expected, options.queries);
+        let args = ["--circuits-after", "many"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        let args = ["--newick"];
+        assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        for distance in ["NaN", "inf", "-1"] {
+            let args = ["--circuits-at", distance];
+            assert!(Options::parse(args.into_iter().map(String::from)).is_err());
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::connection::Connection;
+use crate::disjoint_set::DisjointSet;
+use crate::junction::Junction;
+use std::fmt::Write;
+
+/// The shortest set of connections that joins every junction into one
+/// circuit, in the order Kruskal's algorithm adds them. If the connections
+/// run out first, this is only a forest of the circuits that were made.
+#[derive(Debug)]
+pub struct SpanningTree {
+    edges: Vec<Connection>,
+    complete: bool,
+}
+
+impl SpanningTree {
+    /// Takes connections in order of length, keeping each one that joins two
+    /// circuits, until every junction is in a single circuit.
+    pub fn build(connections: impl Iterator<Item = Connection>, total_junctions: usize) -> Self {
+        let mut circuits = DisjointSet::new(total_junctions);
+        let mut edges = Vec::with_capacity(total_junctions.saturating_sub(1));
+        if total_junctions <= 1 {
+            return Self {
+                edges,
+                complete: true,
+            };
+        }
+        for connection in connections {
+            if !circuits.union(connection.first_index, connection.second_index) {
+                continue;
+            }
+            let size = circuits.size(connection.first_index);
+            edges.push(connection);
+            if size == total_junctions {
+                return Self {
+                    edges,
+                    complete: true,
+                };
+            }
+        }
+        Self {
+            edges,
+            complete: false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn edges(&self) -> &[Connection] {
+        &self.edges
+    }
+
+    /// Gets the connection that completed the single circuit, or `None` if
+    /// the junctions never all joined up.
+    #[inline]
+    #[must_use]
+    pub fn final_edge(&self) -> Option<&Connection> {
+        self.edges.last().filter(|_| self.complete)
+    }
+
+    #[must_use]
+    pub fn total_length(&self) -> f64 {
+        self.edges.iter().map(|edge| edge.length).sum()
+    }
+
+    /// Writes the tree as an undirected Graphviz graph, with each junction
+    /// labelled by its coordinates and each edge by its length.
+    #[must_use]
+    pub fn to_dot(&self, junctions: &[Junction]) -> String {
+        let mut dot = String::from("graph spanning_tree {\n");
+        for (index, junction) in junctions.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    j{index} [label=\"{},{},{}\"];",
+                junction.x, junction.y, junction.z
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    j{} -- j{} [label=\"{:.3}\"];",
+                edge.first_index, edge.second_index, edge.length
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes one row per edge, in the order the edges were added.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "order,first_index,first_x,first_y,first_z,second_index,second_x,second_y,second_z,length\n",
+        );
+        for (order, edge) in self.edges.iter().enumerate() {
+            let (first, second) = (edge.first, edge.second);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{:.3}",
+                order + 1,
+                edge.first_index,
+                first.x,
+                first.y,
+                first.z,
+                edge.second_index,
+                second.x,
+                second.y,
+                second.z,
+                edge.length
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::closest_pairs::ClosestPairs;
+    use crate::junction::Junction;
+    use crate::metric::Euclidean;
+    use crate::spanning_tree::SpanningTree;
+
+    #[test]
+    fn test_build() {
+        let junctions = [
+            Junction::from_x_y_z(0, 0, 0),
+            Junction::from_x_y_z(3, 4, 0),
+            Junction::from_x_y_z(0, 1, 0),
+            Junction::from_x_y_z(10, 4, 0),
+        ];
+        let tree = SpanningTree::build(ClosestPairs::new(&junctions, &Euclidean), junctions.len());
+        let edges: Vec<(usize, usize)> = tree
+            .edges()
+            .iter()
+            .map(|e| (e.first_index, e.second_index))
+            .collect();
+        assert_eq!(vec![(0, 2), (1, 2), (1, 3)], edges);
+        let final_edge = tree.final_edge().unwrap();
+        assert_eq!(Junction::from_x_y_z(10, 4, 0), final_edge.second);
+        let expected = 1.0 + 18f64.sqrt() + 7.0;
+        assert!((tree.total_length() - expected).abs() < 1e-9);
+
+        let expected = "\
+graph spanning_tree {
+    j0 [label=\"0,0,0\"];
+    j1 [label=\"3,4,0\"];
+    j2 [label=\"0,1,0\"];
+    j3 [label=\"10,4,0\"];
+    j0 -- j2 [label=\"1.000\"];
+    j1 -- j2 [label=\"4.243\"];
+    j1 -- j3 [label=\"7.000\"];
+}
+";
+        assert_eq!(expected, tree.to_dot(&junctions));
+
+        let csv = tree.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("2,1,3,4,0,2,0,1,0,4.243", lines[2]);
+    }
+
+    #[test]
+    fn test_single_junction() {
+        let junctions = [Junction::from_x_y_z(1, 2, 3)];
+        let tree = SpanningTree::build(ClosestPairs::new(&junctions, &Euclidean), junctions.len());
+        assert!(tree.final_edge().is_none());
+        assert!(tree.total_length().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_incomplete() {
+        let junctions = [
+            Junction::from_x_y_z(0, 0, 0),
+            Junction::from_x_y_z(0, 1, 0),
+            Junction::from_x_y_z(5, 5, 5),
+        ];
+        let connections = ClosestPairs::new(&junctions, &Euclidean).take(1);
+        let tree = SpanningTree::build(connections, junctions.len());
+        assert_eq!(1, tree.edges().len());
+        assert!(tree.final_edge().is_none());
+    }
+}
@@ -0,0 +1,405 @@
+// Limbs are deliberately cut out of the low bits of wider intermediate values.
+#![allow(clippy::cast_possible_truncation)]
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A signed integer of any size, stored as a sign and base 2^32 limbs from
+/// least to most significant. Zero has no limbs and is never negative.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    #[inline]
+    #[must_use]
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Converts to the nearest `f64`, which loses precision past 2^53 and
+    /// becomes infinite past `f64::MAX`.
+    #[must_use]
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.limbs.iter().rev().fold(0f64, |total, limb| {
+            total * 4_294_967_296f64 + f64::from(*limb)
+        });
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    #[must_use]
+    pub fn mul_i64(&self, value: i64) -> Self {
+        let factor = u128::from(value.unsigned_abs());
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 2);
+        let mut carry = 0u128;
+        for limb in &self.limbs {
+            let product = u128::from(*limb) * factor + carry;
+            limbs.push(product as u32);
+            carry = product >> 32;
+        }
+        while carry != 0 {
+            limbs.push(carry as u32);
+            carry >>= 32;
+        }
+        Self::from_parts(self.negative != (value < 0), limbs)
+    }
+
+    /// Divides by the value, truncating towards zero like the built-in
+    /// integers do, so the remainder takes the sign of `self`. Returns `None`
+    /// when dividing by zero.
+    #[must_use]
+    pub fn div_rem_i64(&self, value: i64) -> Option<(Self, i64)> {
+        if value == 0 {
+            return None;
+        }
+        let divisor = u128::from(value.unsigned_abs());
+        let mut limbs = vec![0u32; self.limbs.len()];
+        let mut remainder = 0u128;
+        for (index, limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | u128::from(*limb);
+            limbs[index] = (current / divisor) as u32;
+            remainder = current % divisor;
+        }
+        // The remainder is smaller than |value|, so it always fits.
+        let remainder = remainder as i64;
+        let remainder = if self.negative { -remainder } else { remainder };
+        let quotient = Self::from_parts(self.negative != (value < 0), limbs);
+        Some((quotient, remainder))
+    }
+
+    /// Divides by the other value, truncating towards zero like the built-in
+    /// integers do, so the remainder takes the sign of `self`. Returns `None`
+    /// when dividing by zero.
+    #[must_use]
+    pub fn div_rem(&self, other: &BigInt) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        // Binary long division, bringing down one bit of `self` at a time.
+        let mut quotient = vec![0u32; self.limbs.len()];
+        let mut remainder = Vec::with_capacity(other.limbs.len() + 1);
+        for bit in (0..self.limbs.len() * 32).rev() {
+            let (limb, shift) = (bit / 32, bit % 32);
+            Self::shift_in_bit(&mut remainder, (self.limbs[limb] >> shift) & 1);
+            if Self::compare_magnitudes(&remainder, &other.limbs) != Ordering::Less {
+                remainder = Self::subtract_magnitudes(&remainder, &other.limbs);
+                Self::trim(&mut remainder);
+                quotient[limb] |= 1 << shift;
+            }
+        }
+        let quotient = Self::from_parts(self.negative != other.negative, quotient);
+        Some((quotient, Self::from_parts(self.negative, remainder)))
+    }
+
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        Self::trim(&mut limbs);
+        let negative = negative && !limbs.is_empty();
+        Self { negative, limbs }
+    }
+
+    fn trim(limbs: &mut Vec<u32>) {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+    }
+
+    /// Doubles a magnitude and adds the bit.
+    fn shift_in_bit(limbs: &mut Vec<u32>, bit: u32) {
+        let mut carry = bit;
+        for limb in limbs.iter_mut() {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            limbs.push(carry);
+        }
+    }
+
+    fn compare_magnitudes(first: &[u32], second: &[u32]) -> Ordering {
+        first
+            .len()
+            .cmp(&second.len())
+            .then_with(|| first.iter().rev().cmp(second.iter().rev()))
+    }
+
+    fn add_magnitudes(first: &[u32], second: &[u32]) -> Vec<u32> {
+        let (longer, shorter) = if first.len() >= second.len() {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let mut limbs = Vec::with_capacity(longer.len() + 1);
+        let mut carry = 0u64;
+        for (index, limb) in longer.iter().enumerate() {
+            let other = shorter.get(index).copied().unwrap_or(0);
+            let sum = u64::from(*limb) + u64::from(other) + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        if carry != 0 {
+            limbs.push(carry as u32);
+        }
+        limbs
+    }
+
+    /// Subtracts the smaller magnitude from the larger one.
+    fn subtract_magnitudes(larger: &[u32], smaller: &[u32]) -> Vec<u32> {
+        let mut limbs = Vec::with_capacity(larger.len());
+        let mut borrow = false;
+        for (index, limb) in larger.iter().enumerate() {
+            let other = smaller.get(index).copied().unwrap_or(0);
+            let (difference, first_borrow) = limb.overflowing_sub(other);
+            let (difference, second_borrow) = difference.overflowing_sub(u32::from(borrow));
+            borrow = first_borrow || second_borrow;
+            limbs.push(difference);
+        }
+        limbs
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        let limbs = vec![magnitude as u32, (magnitude >> 32) as u32];
+        Self::from_parts(value < 0, limbs)
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        let limbs = vec![value as u32, (value >> 32) as u32];
+        Self::from_parts(false, limbs)
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            let limbs = BigInt::add_magnitudes(&self.limbs, &other.limbs);
+            return BigInt::from_parts(self.negative, limbs);
+        }
+        match BigInt::compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                let limbs = BigInt::subtract_magnitudes(&other.limbs, &self.limbs);
+                BigInt::from_parts(other.negative, limbs)
+            }
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => {
+                let limbs = BigInt::subtract_magnitudes(&self.limbs, &other.limbs);
+                BigInt::from_parts(self.negative, limbs)
+            }
+        }
+    }
+}
+
+impl AddAssign<&BigInt> for BigInt {
+    fn add_assign(&mut self, other: &BigInt) {
+        *self = &*self + other;
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (first_index, first) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (second_index, second) in other.limbs.iter().enumerate() {
+                let index = first_index + second_index;
+                // At most (2^32 - 1)^2 + 2 * (2^32 - 1), which fits in a u64
+                let product =
+                    u64::from(*first) * u64::from(*second) + u64::from(limbs[index]) + carry;
+                limbs[index] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[first_index + other.limbs.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, limbs)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => Self::compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => Self::compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const CHUNK: i64 = 1_000_000_000;
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off 9 decimal digits at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut remaining = Self::from_parts(false, self.limbs.clone());
+        while !remaining.is_zero() {
+            let Some((quotient, remainder)) = remaining.div_rem_i64(CHUNK) else {
+                unreachable!("The divisor is not zero");
+            };
+            chunks.push(remainder);
+            remaining = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::big_int::BigInt;
+
+    #[test]
+    fn test_to_f64() {
+        assert!(BigInt::zero().to_f64().abs() < f64::EPSILON);
+        assert!((BigInt::from(-12_345i64).to_f64() + 12_345f64).abs() < f64::EPSILON);
+        let large = BigInt::from(u64::MAX).mul_i64(4);
+        assert!((large.to_f64() / 7.378_697_629_483_821e19 - 1f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("0", BigInt::zero().to_string());
+        assert_eq!("-42", BigInt::from(-42i64).to_string());
+        assert_eq!("18446744073709551615", BigInt::from(u64::MAX).to_string());
+        assert_eq!("-9223372036854775808", BigInt::from(i64::MIN).to_string());
+    }
+
+    #[test]
+    fn test_add_and_subtract() {
+        let first = BigInt::from(u64::MAX);
+        let second = BigInt::from(1i64);
+        let sum = &first + &second;
+        assert_eq!("18446744073709551616", sum.to_string());
+        assert_eq!(first, &sum - &second);
+        assert_eq!(
+            "-5",
+            (&BigInt::from(3i64) - &BigInt::from(8i64)).to_string()
+        );
+        assert_eq!(BigInt::zero(), &BigInt::from(-7i64) + &BigInt::from(7i64));
+    }
+
+    #[test]
+    fn test_mul_i64() {
+        let mut value = BigInt::from(1i64);
+        for _ in 0..30 {
+            value = value.mul_i64(10);
+        }
+        assert_eq!(format!("1{}", "0".repeat(30)), value.to_string());
+        assert_eq!(
+            format!("-3{}", "0".repeat(30)),
+            value.mul_i64(-3).to_string()
+        );
+        assert!(value.mul_i64(0).is_zero());
+    }
+
+    #[test]
+    fn test_div_rem_i64() {
+        let value = BigInt::from(u64::MAX).mul_i64(1_000);
+        let (quotient, remainder) = value.div_rem_i64(1_000).unwrap();
+        assert_eq!(BigInt::from(u64::MAX), quotient);
+        assert_eq!(0, remainder);
+        let (quotient, remainder) = BigInt::from(-7i64).div_rem_i64(2).unwrap();
+        assert_eq!(BigInt::from(-3i64), quotient);
+        assert_eq!(-1, remainder);
+        assert!(value.div_rem_i64(0).is_none());
+    }
+
+    #[test]
+    fn test_mul() {
+        let first = BigInt::from(u64::MAX);
+        let square = &first * &first;
+        assert_eq!(
+            "340282366920938463426481119284349108225",
+            square.to_string()
+        );
+        assert_eq!(square, &first.mul_i64(-1).mul_i64(-1) * &first);
+        let negative = &BigInt::from(-3i64) * &first;
+        assert_eq!(first.mul_i64(-3), negative);
+        assert!((&first * &BigInt::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let first = BigInt::from(u64::MAX);
+        let square = &first * &first;
+        let (quotient, remainder) = (&square + &BigInt::from(5i64)).div_rem(&first).unwrap();
+        assert_eq!(first, quotient);
+        assert_eq!(BigInt::from(5i64), remainder);
+        let (quotient, remainder) = BigInt::from(-7i64).div_rem(&BigInt::from(2i64)).unwrap();
+        assert_eq!(BigInt::from(-3i64), quotient);
+        assert_eq!(BigInt::from(-1i64), remainder);
+        let (quotient, remainder) = BigInt::from(7i64).div_rem(&-&first).unwrap();
+        assert!(quotient.is_zero());
+        assert_eq!(BigInt::from(7i64), remainder);
+        assert!(first.div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let big = BigInt::from(u64::MAX);
+        let negative_big = -&big;
+        assert!(negative_big < BigInt::from(-1i64));
+        assert!(BigInt::from(-1i64) < BigInt::zero());
+        assert!(BigInt::zero() < big);
+        assert_eq!(
+            BigInt::from(5i64),
+            BigInt::from(5i64).max(BigInt::from(-5i64))
+        );
+    }
+}
@@ -1,4 +1,6 @@
 mod advent_error;
+mod big_int;
 
 pub use advent_error::AdventError;
 pub use advent_error::Result;
+pub use big_int::BigInt;